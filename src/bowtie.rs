@@ -0,0 +1,109 @@
+use serde::Serialize;
+
+use crate::graph::CompactGraph;
+
+/// The regions of the bow-tie structure of a web graph.
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Region {
+    /// The largest strongly connected component.
+    Core,
+    /// Pages that can reach the core, but can't be reached from it.
+    In,
+    /// Pages that can be reached from the core, but can't reach it.
+    Out,
+    /// Pages that can be reached from IN or can reach OUT, but not both.
+    Tendrils,
+    /// Pages that can be reached from IN and can reach OUT without passing the core.
+    Tubes,
+    Disconnected,
+}
+
+impl Region {
+    pub const ALL: [Region; 6] = [
+        Region::Core,
+        Region::In,
+        Region::Out,
+        Region::Tendrils,
+        Region::Tubes,
+        Region::Disconnected,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Region::Core => "Core",
+            Region::In => "IN",
+            Region::Out => "OUT",
+            Region::Tendrils => "Tendrils",
+            Region::Tubes => "Tubes",
+            Region::Disconnected => "Disconnected",
+        }
+    }
+}
+
+/// Sorts every node into its region, given the nodes of the core.
+/// `reverse` must be the reverse graph of `graph`.
+pub fn regions(graph: &CompactGraph, reverse: &CompactGraph, core: &[bool]) -> Vec<Region> {
+    let _progress = crate::progress::msg("Finding the bow-tie structure");
+    let nodes = 0..graph.len() as u32;
+    let core_nodes: Vec<_> = nodes.clone().filter(|&n| core[n as usize]).collect();
+    let out = reach(graph, &core_nodes, |_| true);
+    let into = reach(reverse, &core_nodes, |_| true);
+
+    let mut regions: Vec<_> = nodes
+        .clone()
+        .map(
+            |n| match (core[n as usize], into[n as usize], out[n as usize]) {
+                (true, _, _) => Region::Core,
+                (_, true, _) => Region::In,
+                (_, _, true) => Region::Out,
+                _ => Region::Disconnected,
+            },
+        )
+        .collect();
+
+    // Only look at the remaining pages, anything reached through the core is already in OUT.
+    let remaining = |n: u32| regions[n as usize] == Region::Disconnected;
+    let in_nodes: Vec<_> = nodes
+        .clone()
+        .filter(|&n| regions[n as usize] == Region::In)
+        .collect();
+    let out_nodes: Vec<_> = nodes
+        .clone()
+        .filter(|&n| regions[n as usize] == Region::Out)
+        .collect();
+    let from_in = reach(graph, &in_nodes, remaining);
+    let to_out = reach(reverse, &out_nodes, remaining);
+
+    for n in nodes {
+        let n = n as usize;
+        if regions[n] != Region::Disconnected {
+            continue;
+        }
+        regions[n] = match (from_in[n], to_out[n]) {
+            (true, true) => Region::Tubes,
+            (true, false) | (false, true) => Region::Tendrils,
+            (false, false) => Region::Disconnected,
+        };
+    }
+
+    regions
+}
+
+/// Marks all nodes reachable from the sources, only entering nodes for which `allowed` is true.
+/// The sources themselves are not marked unless one of them is reached from another.
+fn reach(graph: &CompactGraph, sources: &[u32], allowed: impl Fn(u32) -> bool) -> Vec<bool> {
+    let mut reached = vec![false; graph.len()];
+    let mut todo = sources.to_vec();
+
+    while let Some(curr) = todo.pop() {
+        for &n in graph.links(curr) {
+            if !reached[n as usize] && allowed(n) {
+                reached[n as usize] = true;
+                todo.push(n);
+            }
+        }
+    }
+
+    reached
+}
@@ -0,0 +1,96 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::graph::{Graph, PathError};
+use crate::workspace::Workspace;
+
+/// The category tree built from the `categorylinks` table.
+pub struct Categories {
+    /// Edges from pages and subcategories to the categories they are in.
+    parents: Graph,
+    /// Edges from categories to their pages and subcategories.
+    children: Graph,
+}
+
+impl Categories {
+    pub fn load(workspace: &Workspace) -> Categories {
+        eprintln!("Loading category graph ..");
+        Categories {
+            parents: Graph::load_file(workspace.path(crate::CATEGORIES)),
+            children: Graph::load_file(workspace.path(crate::CATEGORIES_REVERSE)),
+        }
+    }
+
+    pub fn categories_of(&self, page: usize) -> Vec<usize> {
+        match self.parents.graph.get(&page) {
+            Some(categories) => categories.iter().copied().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Walks up the category tree and returns all reachable categories that are
+    /// not in any other category, together with their distance from the page.
+    pub fn find_top_level(&self, page: usize) -> Vec<(usize, usize)> {
+        let mut visited = HashSet::new();
+        let mut todo = VecDeque::new();
+        let mut result = Vec::new();
+
+        visited.insert(page);
+        todo.push_back((page, 0));
+
+        while let Some((curr, dist)) = todo.pop_front() {
+            match self.parents.graph.get(&curr) {
+                Some(parents) => {
+                    for &n in parents {
+                        if visited.insert(n) {
+                            todo.push_back((n, dist + 1));
+                        }
+                    }
+                }
+                None if curr != page => result.push((curr, dist)),
+                None => (),
+            }
+        }
+
+        result
+    }
+
+    /// Finds the shortest path between two pages in the category tree,
+    /// going up to parent categories as well as down to members.
+    pub fn find_shortest_path(&self, start: usize, end: usize) -> Result<Vec<usize>, PathError> {
+        for page in [start, end] {
+            if !self.parents.graph.contains_key(&page) && !self.children.graph.contains_key(&page) {
+                return Err(PathError::UnknownPage(page));
+            }
+        }
+        let mut came_from = HashMap::new();
+        let mut todo = VecDeque::new();
+
+        todo.push_back(start);
+        came_from.insert(start, start);
+
+        while let Some(curr) = todo.pop_front() {
+            if curr == end {
+                let mut path = vec![end];
+                let mut curr = end;
+                while curr != start {
+                    curr = came_from[&curr];
+                    path.push(curr);
+                }
+                path.reverse();
+                return Ok(path);
+            }
+
+            let parents = self.parents.graph.get(&curr).into_iter().flatten();
+            let children = self.children.graph.get(&curr).into_iter().flatten();
+            for &n in parents.chain(children) {
+                if let Entry::Vacant(e) = came_from.entry(n) {
+                    e.insert(curr);
+                    todo.push_back(n);
+                }
+            }
+        }
+
+        Err(PathError::NoPath)
+    }
+}
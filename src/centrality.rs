@@ -0,0 +1,281 @@
+use rayon::prelude::*;
+
+use crate::graph::CompactGraph;
+
+/// Estimates the betweenness centrality of all nodes, i.e. on how many shortest paths
+/// between two other nodes they lie, counting each path as 1 / number of shortest paths.
+///
+/// Runs Brandes' algorithm from the given sources only and scales the result up to all nodes,
+/// so the estimate is exact if every node is a source.
+pub fn betweenness(graph: &CompactGraph, sources: &[u32]) -> Vec<f64> {
+    let _progress = crate::progress::msg("Computing betweenness");
+    let n = graph.len();
+    let mut scores = sources
+        .par_iter()
+        .fold(
+            || Brandes::new(n),
+            |mut brandes, &source| {
+                brandes.add_source(graph, source);
+                brandes
+            },
+        )
+        .map(|brandes| brandes.scores)
+        .reduce(
+            || vec![0.0; n],
+            |mut a, b| {
+                a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+                a
+            },
+        );
+
+    if !sources.is_empty() {
+        let scale = n as f64 / sources.len() as f64;
+        scores.par_iter_mut().for_each(|score| *score *= scale);
+    }
+    scores
+}
+
+/// How close a node is to all others.
+pub struct Closeness {
+    /// The number of reached nodes divided by the average distance to them,
+    /// scaled by the fraction of nodes that are reached at all (Wasserman and Faust).
+    pub closeness: f64,
+    /// The average of 1 / distance over all other nodes, unreachable ones counting as 0.
+    pub harmonic: f64,
+    /// The number of other nodes that are reachable.
+    pub reachable: usize,
+}
+
+impl Closeness {
+    /// Combines the reached nodes and the sums of their distances and inverse distances.
+    fn new(n: usize, reachable: f64, distances: f64, inverse_distances: f64) -> Closeness {
+        let others = n.saturating_sub(1).max(1) as f64;
+        Closeness {
+            closeness: if distances > 0.0 {
+                reachable / others * reachable / distances
+            } else {
+                0.0
+            },
+            harmonic: inverse_distances / others,
+            reachable: reachable.round() as usize,
+        }
+    }
+}
+
+/// The exact closeness of a node to the nodes it links to, directly or indirectly.
+pub fn closeness(graph: &CompactGraph, node: u32) -> Closeness {
+    let mut dist = vec![u32::MAX; graph.len()];
+    let mut order = Vec::new();
+    bfs(graph, node, &mut dist, &mut order);
+    let (mut distances, mut inverse_distances) = (0.0, 0.0);
+    for &other in &order[1..] {
+        let d = dist[other as usize] as f64;
+        distances += d;
+        inverse_distances += 1.0 / d;
+    }
+    Closeness::new(
+        graph.len(),
+        (order.len() - 1) as f64,
+        distances,
+        inverse_distances,
+    )
+}
+
+/// Estimates the closeness of all nodes from the distances to the given targets,
+/// found by searching the reverse graph from each of them.
+/// The estimate is exact if every node is a target.
+pub fn estimate_closeness(reverse: &CompactGraph, targets: &[u32]) -> Vec<Closeness> {
+    let _progress = crate::progress::msg("Computing closeness");
+    let n = reverse.len();
+    // The number of reached targets and the sums of the distances and inverse distances.
+    type Sums = Vec<(f64, f64, f64)>;
+    let sums = targets
+        .par_iter()
+        .fold(
+            || (vec![(0.0, 0.0, 0.0); n], vec![u32::MAX; n], Vec::new()),
+            |(mut sums, mut dist, mut order): (Sums, Vec<u32>, Vec<u32>), &target| {
+                bfs(reverse, target, &mut dist, &mut order);
+                for &node in &order[1..] {
+                    let d = dist[node as usize] as f64;
+                    let sum = &mut sums[node as usize];
+                    sum.0 += 1.0;
+                    sum.1 += d;
+                    sum.2 += 1.0 / d;
+                }
+                for node in order.drain(..) {
+                    dist[node as usize] = u32::MAX;
+                }
+                (sums, dist, order)
+            },
+        )
+        .map(|(sums, _, _)| sums)
+        .reduce(
+            || vec![(0.0, 0.0, 0.0); n],
+            |mut a, b| {
+                for (a, b) in a.iter_mut().zip(b) {
+                    a.0 += b.0;
+                    a.1 += b.1;
+                    a.2 += b.2;
+                }
+                a
+            },
+        );
+
+    let mut is_target = vec![false; n];
+    for &target in targets {
+        is_target[target as usize] = true;
+    }
+    sums.into_par_iter()
+        .enumerate()
+        .map(|(node, (reached, distances, inverse_distances))| {
+            // A node's distance to itself doesn't count, so it has one target less.
+            let others = targets.len() - is_target[node] as usize;
+            let scale = if others > 0 {
+                (n - 1) as f64 / others as f64
+            } else {
+                0.0
+            };
+            Closeness::new(
+                n,
+                reached * scale,
+                distances * scale,
+                inverse_distances * scale,
+            )
+        })
+        .collect()
+}
+
+/// A breadth-first search, which records the distances in `dist`
+/// and the reached nodes in the order of their distance in `order`.
+/// `dist` must be `u32::MAX` for all nodes and `order` empty before.
+pub fn bfs(graph: &CompactGraph, source: u32, dist: &mut [u32], order: &mut Vec<u32>) {
+    dist[source as usize] = 0;
+    order.push(source);
+    let mut i = 0;
+    while i < order.len() {
+        let node = order[i];
+        i += 1;
+        for &link in graph.links(node) {
+            if dist[link as usize] == u32::MAX {
+                dist[link as usize] = dist[node as usize] + 1;
+                order.push(link);
+            }
+        }
+    }
+}
+
+/// The state of one thread running Brandes' algorithm.
+/// The buffers are reused between sources and only reset where they were touched.
+struct Brandes {
+    scores: Vec<f64>,
+    /// The distance from the current source, `u32::MAX` if not reached.
+    dist: Vec<u32>,
+    /// The number of shortest paths from the current source.
+    paths: Vec<f64>,
+    /// The dependency of the current source on each node.
+    dependency: Vec<f64>,
+    /// The reached nodes in the order of their distance.
+    order: Vec<u32>,
+}
+
+impl Brandes {
+    fn new(n: usize) -> Brandes {
+        Brandes {
+            scores: vec![0.0; n],
+            dist: vec![u32::MAX; n],
+            paths: vec![0.0; n],
+            dependency: vec![0.0; n],
+            order: Vec::new(),
+        }
+    }
+
+    fn add_source(&mut self, graph: &CompactGraph, source: u32) {
+        self.dist[source as usize] = 0;
+        self.paths[source as usize] = 1.0;
+        self.order.push(source);
+        let mut i = 0;
+        while i < self.order.len() {
+            let node = self.order[i] as usize;
+            i += 1;
+            for &link in graph.links(node as u32) {
+                let link = link as usize;
+                if self.dist[link] == u32::MAX {
+                    self.dist[link] = self.dist[node] + 1;
+                    self.order.push(link as u32);
+                }
+                if self.dist[link] == self.dist[node] + 1 {
+                    self.paths[link] += self.paths[node];
+                }
+            }
+        }
+
+        // Going backwards, every node is handled after all nodes one step further away.
+        for &node in self.order.iter().rev() {
+            let node = node as usize;
+            let mut dependency = 0.0;
+            for &link in graph.links(node as u32) {
+                let link = link as usize;
+                if self.dist[link] == self.dist[node] + 1 {
+                    dependency +=
+                        self.paths[node] / self.paths[link] * (1.0 + self.dependency[link]);
+                }
+            }
+            self.dependency[node] = dependency;
+            if node != source as usize {
+                self.scores[node] += dependency;
+            }
+        }
+
+        for node in self.order.drain(..) {
+            let node = node as usize;
+            self.dist[node] = u32::MAX;
+            self.paths[node] = 0.0;
+            self.dependency[node] = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::{graphs, random_links};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn betweenness_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..50 {
+            let n = 10;
+            let (graph, reverse) = graphs(&random_links(&mut rng, n, 25));
+            let pages: Vec<_> = (0..n).collect();
+            let compact = CompactGraph::new(&graph, &pages);
+            let sources: Vec<_> = (0..n as u32).collect();
+            let scores = betweenness(&compact, &sources);
+
+            // Every shortest path between two pages adds 1 / the number of those paths
+            // to all pages in between.
+            let mut expected = vec![0.0; n];
+            for (start, end) in (0..n).flat_map(|start| (0..n).map(move |end| (start, end))) {
+                if start == end {
+                    continue;
+                }
+                if let Ok(paths) = graph.all_shortest_paths(&reverse, start, end) {
+                    let count = paths.count() as f64;
+                    for path in paths.list(usize::MAX) {
+                        for &page in &path[1..path.len() - 1] {
+                            expected[page] += 1.0 / count;
+                        }
+                    }
+                }
+            }
+            for (score, expected) in scores.iter().zip(&expected) {
+                assert!(
+                    (score - expected).abs() < 1e-9,
+                    "{:?} != {:?}",
+                    scores,
+                    expected
+                );
+            }
+        }
+    }
+}
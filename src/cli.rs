@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::output::Format;
+
+pub const USAGE: &str = "\
+Usage: wiki-analyzer [OPTIONS] [COMMAND [ARGS]...]
+
+Without a command an interactive prompt is started.
+
+Options:
+    --data-dir <DIR>        Directory containing the dumps (default: data)
+    --wiki <PREFIX>         Dump prefix of the wiki to use, e.g. enwiki or enwiki-20261001
+    --namespaces <IDS>      Comma-separated namespaces to include in the link graph (default: 0)
+    --format <FORMAT>       Output format: text, json or jsonl (default: text)
+                            Can also be given to single commands
+    -h, --help              Print this help
+    -V, --version           Print the version";
+
+pub enum Error {
+    /// Invalid or missing arguments. Printed by the caller together with a hint.
+    Usage(String),
+    /// The command failed. The reason was already printed.
+    Failed,
+    /// There is no path between the pages. The result was already printed.
+    NoPath,
+}
+
+pub type CmdResult = Result<(), Error>;
+
+pub struct Options {
+    pub data_dir: PathBuf,
+    pub wiki: Option<String>,
+    pub namespaces: HashSet<i64>,
+    pub format: Format,
+    /// The command to run non-interactively together with its arguments.
+    pub command: Option<(String, Input)>,
+}
+
+impl Options {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options {
+            data_dir: PathBuf::from(crate::DATA_DIR),
+            wiki: None,
+            namespaces: [0].iter().copied().collect(),
+            format: Format::Text,
+            command: None,
+        };
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or(format!("Missing value for '{}'", name));
+            match arg.as_str() {
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    println!();
+                    crate::print_commands();
+                    std::process::exit(0);
+                }
+                "-V" | "--version" => {
+                    println!("wiki-analyzer {}", env!("CARGO_PKG_VERSION"));
+                    std::process::exit(0);
+                }
+                "--data-dir" => options.data_dir = PathBuf::from(value(&arg)?),
+                "--wiki" => options.wiki = Some(value(&arg)?),
+                "--namespaces" => {
+                    let list = value(&arg)?;
+                    options.namespaces = list
+                        .split(',')
+                        .map(|ns| ns.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| format!("Invalid namespace list '{}'", list))?;
+                }
+                "--format" => options.format = value(&arg)?.parse()?,
+                _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+                _ => {
+                    let input = Input::new(args.collect(), false);
+                    options.command = Some((arg, input));
+                    break;
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// The arguments of a command.
+///
+/// Options are taken out by name first, the remaining arguments are positional.
+/// In interactive mode missing positional arguments are asked for instead.
+pub struct Input {
+    args: Vec<String>,
+    pub interactive: bool,
+}
+
+impl Input {
+    pub fn new(args: Vec<String>, interactive: bool) -> Input {
+        Input { args, interactive }
+    }
+
+    /// Splits a line typed into the interactive prompt into the command and its arguments.
+    /// Arguments containing spaces can be quoted with `"` or `'`.
+    pub fn parse_line(line: &str) -> Result<(String, Input), String> {
+        let mut args = Vec::new();
+        let mut chars = line.trim().chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+                continue;
+            }
+            let mut arg = String::new();
+            if c == '"' || c == '\'' {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some(q) if q == c && chars.peek().is_none_or(|n| n.is_whitespace()) => {
+                            break
+                        }
+                        Some(ch) => arg.push(ch),
+                        None => return Err(format!("Missing closing {}", c)),
+                    }
+                }
+            } else {
+                while let Some(&ch) = chars.peek() {
+                    if ch.is_whitespace() {
+                        break;
+                    }
+                    arg.push(ch);
+                    chars.next();
+                }
+            }
+            args.push(arg);
+        }
+
+        if args.is_empty() {
+            return Ok((String::new(), Input::new(args, true)));
+        }
+        let command = args.remove(0);
+        Ok((command, Input::new(args, true)))
+    }
+
+    /// Takes out a boolean option like `--reverse`.
+    pub fn flag(&mut self, name: &str) -> bool {
+        let flag = format!("--{}", name);
+        let len = self.args.len();
+        self.args.retain(|arg| *arg != flag);
+        self.args.len() != len
+    }
+
+    /// Takes out all occurrences of an option with a value,
+    /// given as either `--name value` or `--name=value`.
+    pub fn values(&mut self, name: &str) -> Result<Vec<String>, Error> {
+        let option = format!("--{}", name);
+        let prefix = format!("--{}=", name);
+        let mut values = Vec::new();
+        let mut i = 0;
+
+        while i < self.args.len() {
+            if self.args[i] == option {
+                if i + 1 == self.args.len() {
+                    return Err(Error::Usage(format!("Missing value for '{}'", option)));
+                }
+                self.args.remove(i);
+                values.push(self.args.remove(i));
+            } else if let Some(value) = self.args[i].strip_prefix(&prefix) {
+                values.push(value.to_owned());
+                self.args.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Takes out an option with a value. If it is given multiple times, the last one wins.
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self.values(name)?.pop())
+    }
+
+    pub fn parsed<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, Error> {
+        match self.value(name)? {
+            Some(value) => match value.parse() {
+                Ok(value) => Ok(Some(value)),
+                Err(_) => Err(Error::Usage(format!(
+                    "Invalid value '{}' for '--{}'",
+                    value, name
+                ))),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Takes the next positional argument.
+    pub fn next(&mut self) -> Result<Option<String>, Error> {
+        match self.args.first() {
+            Some(arg) if arg.starts_with("--") => {
+                Err(Error::Usage(format!("Unknown option '{}'", arg)))
+            }
+            Some(_) => Ok(Some(self.args.remove(0))),
+            None => Ok(None),
+        }
+    }
+
+    /// Fails if there are arguments left that no one asked for.
+    pub fn finish(&self) -> CmdResult {
+        match self.args.first() {
+            Some(arg) if arg.starts_with("--") => {
+                Err(Error::Usage(format!("Unknown option '{}'", arg)))
+            }
+            Some(arg) => Err(Error::Usage(format!("Unexpected argument '{}'", arg))),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use crate::graph::CompactGraph;
+use crate::workspace::Workspace;
+
+/// The strongly connected components of the link graph,
+/// i.e. the groups of pages that can all reach each other.
+pub struct Components {
+    /// The component of each page, numbered by size with 0 being the largest.
+    labels: HashMap<usize, u32>,
+    /// The number of pages in each component.
+    sizes: Vec<usize>,
+}
+
+impl Components {
+    pub fn compute(graph: &CompactGraph) -> Components {
+        let _progress = crate::progress::msg("Finding strongly connected components");
+        let components = strongly_connected(graph);
+
+        // Number the components by size and then by their first page for stable labels.
+        let count = components.iter().max().map_or(0, |&c| c as usize + 1);
+        let mut sizes = vec![0; count];
+        let mut first = vec![usize::MAX; count];
+        for (node, &c) in components.iter().enumerate() {
+            sizes[c as usize] += 1;
+            first[c as usize] = first[c as usize].min(graph.pages[node]);
+        }
+        let mut order: Vec<_> = (0..count).collect();
+        order.sort_unstable_by_key(|&c| (std::cmp::Reverse(sizes[c]), first[c]));
+        let mut relabel = vec![0; count];
+        for (label, &c) in order.iter().enumerate() {
+            relabel[c] = label as u32;
+        }
+
+        let labels = components
+            .iter()
+            .enumerate()
+            .map(|(node, &c)| (graph.pages[node], relabel[c as usize]))
+            .collect();
+        Components::from_labels(labels)
+    }
+
+    pub fn load(workspace: &Workspace) -> Components {
+        let _progress = crate::progress::msg("Loading strongly connected components");
+        let file = File::open(workspace.path(crate::COMPONENTS)).unwrap();
+        let labels = bincode::deserialize_from(BufReader::new(file)).unwrap();
+        Components::from_labels(labels)
+    }
+
+    pub fn save(&self, workspace: &Workspace) {
+        let writer = BufWriter::new(File::create(workspace.path(crate::COMPONENTS)).unwrap());
+        bincode::serialize_into(writer, &self.labels).unwrap();
+    }
+
+    fn from_labels(labels: HashMap<usize, u32>) -> Components {
+        let mut sizes = Vec::new();
+        for &c in labels.values() {
+            if c as usize >= sizes.len() {
+                sizes.resize(c as usize + 1, 0);
+            }
+            sizes[c as usize] += 1;
+        }
+        Components { labels, sizes }
+    }
+
+    /// The component of a page, if it is part of the link graph.
+    pub fn of(&self, page: usize) -> Option<u32> {
+        self.labels.get(&page).copied()
+    }
+
+    pub fn size(&self, component: u32) -> usize {
+        self.sizes[component as usize]
+    }
+
+    /// The number of components.
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// The number of pages in all components.
+    pub fn pages(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// The pages of the components with the given labels, sorted by ID.
+    pub fn members(&self, components: std::ops::Range<u32>) -> Vec<Vec<usize>> {
+        let mut members = vec![Vec::new(); components.len()];
+        for (&page, &c) in &self.labels {
+            if components.contains(&c) {
+                members[(c - components.start) as usize].push(page);
+            }
+        }
+        for pages in &mut members {
+            pages.sort_unstable();
+        }
+        members
+    }
+}
+
+/// Finds the strongly connected components with Tarjan's algorithm and returns the component
+/// of each node. The depth-first search keeps its own stack, so deep graphs can't overflow it.
+fn strongly_connected(graph: &CompactGraph) -> Vec<u32> {
+    const NONE: u32 = u32::MAX;
+    let n = graph.len();
+    let mut index = vec![NONE; n];
+    let mut low = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut component = vec![NONE; n];
+    let mut next_index = 0;
+    let mut next_component = 0;
+    // The nodes whose links are being visited, together with the position of the next link.
+    let mut calls: Vec<(u32, usize)> = Vec::new();
+
+    for root in 0..n as u32 {
+        if index[root as usize] != NONE {
+            continue;
+        }
+        calls.push((root, 0));
+
+        while let Some(&(node, pos)) = calls.last() {
+            let i = node as usize;
+            if pos == 0 {
+                index[i] = next_index;
+                low[i] = next_index;
+                next_index += 1;
+                stack.push(node);
+                on_stack[i] = true;
+            }
+
+            let links = graph.links(node);
+            if let Some(&next) = links.get(pos) {
+                calls.last_mut().unwrap().1 += 1;
+                let j = next as usize;
+                if index[j] == NONE {
+                    calls.push((next, 0));
+                } else if on_stack[j] {
+                    low[i] = low[i].min(index[j]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent as usize] = low[parent as usize].min(low[i]);
+            }
+            if low[i] == index[i] {
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member as usize] = false;
+                    component[member as usize] = next_component;
+                    if member == node {
+                        break;
+                    }
+                }
+                next_component += 1;
+            }
+        }
+    }
+
+    component
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::{graphs, random_links};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn compact(pages: usize, links: &[(usize, usize)]) -> CompactGraph {
+        let (graph, _) = graphs(links);
+        let pages: Vec<_> = (0..pages).collect();
+        CompactGraph::new(&graph, &pages)
+    }
+
+    /// Which nodes each node can reach, by a search from every node.
+    fn reachable(graph: &CompactGraph) -> Vec<Vec<bool>> {
+        (0..graph.len() as u32)
+            .map(|start| {
+                let mut seen = vec![false; graph.len()];
+                let mut todo = vec![start];
+                seen[start as usize] = true;
+                while let Some(node) = todo.pop() {
+                    for &link in graph.links(node) {
+                        if !seen[link as usize] {
+                            seen[link as usize] = true;
+                            todo.push(link);
+                        }
+                    }
+                }
+                seen
+            })
+            .collect()
+    }
+
+    #[test]
+    fn components_match_mutual_reachability() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for links in &[5, 15, 30, 60] {
+            for _ in 0..50 {
+                let graph = compact(20, &random_links(&mut rng, 20, *links));
+                let reach = reachable(&graph);
+                let components = strongly_connected(&graph);
+                for a in 0..graph.len() {
+                    for b in 0..graph.len() {
+                        assert_eq!(
+                            components[a] == components[b],
+                            reach[a][b] && reach[b][a],
+                            "nodes {} and {}",
+                            a,
+                            b
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn labels_are_ordered_by_size() {
+        // {0, 1, 2} and {3, 4} are cycles, 5 only links to them.
+        let links = [(0, 1), (1, 2), (2, 0), (3, 4), (4, 3), (5, 0), (5, 3)];
+        let components = Components::compute(&compact(6, &links));
+        assert_eq!(components.count(), 3);
+        assert_eq!(components.of(0), Some(0));
+        assert_eq!(components.of(2), Some(0));
+        assert_eq!(components.of(4), Some(1));
+        assert_eq!(components.of(5), Some(2));
+        assert_eq!(components.members(0..2), vec![vec![0, 1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn long_cycle_does_not_overflow_the_stack() {
+        let n = 200_000;
+        let links: Vec<_> = (0..n).map(|i| (i, (i + 1) % n)).collect();
+        let components = strongly_connected(&compact(n, &links));
+        assert!(components.iter().all(|&c| c == components[0]));
+    }
+}
@@ -0,0 +1,196 @@
+use std::cmp::Reverse;
+
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+
+use crate::centrality::bfs;
+use crate::graph::CompactGraph;
+
+/// The exact diameter and radius of a strongly connected graph.
+pub struct Extremes {
+    /// The longest shortest path, going from `start` to `end`.
+    pub diameter: usize,
+    pub start: u32,
+    pub end: u32,
+    /// The smallest number of steps needed to reach all nodes from a single one, the `center`.
+    pub radius: usize,
+    pub center: u32,
+    /// The number of nodes searched from, each both forward and backward.
+    pub searches: usize,
+}
+
+/// Bounds for the eccentricities of a node, i.e. its distance to the furthest node
+/// (forward) and the distance of the node furthest from it (backward).
+#[derive(Clone, Copy)]
+struct Bounds {
+    forward_lower: usize,
+    forward_upper: usize,
+    backward_lower: usize,
+    backward_upper: usize,
+}
+
+/// Computes the diameter and radius of a strongly connected graph with the bounding algorithm
+/// of Takes and Kosters: each search from a node narrows down the eccentricities of all others,
+/// so usually only a few nodes have to be searched from instead of all.
+pub fn diameter_and_radius(graph: &CompactGraph, reverse: &CompactGraph) -> Extremes {
+    let n = graph.len();
+    let degrees: Vec<_> = (0..n as u32)
+        .map(|node| graph.links(node).len() + reverse.links(node).len())
+        .collect();
+    let unknown = Bounds {
+        forward_lower: 0,
+        forward_upper: usize::MAX,
+        backward_lower: 0,
+        backward_upper: usize::MAX,
+    };
+    let mut bounds = vec![unknown; n];
+    // The longest distance found so far, which is a lower bound for the diameter.
+    let (mut diameter, mut start, mut end) = (0, 0, 0);
+    let mut searches = 0;
+
+    let progress = ProgressBar::new_spinner();
+    progress.set_style(ProgressStyle::default_spinner().template(" [{elapsed_precise}] {msg}"));
+
+    loop {
+        let forward_max = bounds.par_iter().map(|b| b.forward_upper).max();
+        let backward_max = bounds.par_iter().map(|b| b.backward_upper).max();
+        let diameter_upper = forward_max.min(backward_max).unwrap_or(0);
+        let radius_lower = bounds
+            .par_iter()
+            .map(|b| b.forward_lower)
+            .min()
+            .unwrap_or(0);
+        let (radius_upper, center) = (0..n)
+            .into_par_iter()
+            .map(|node| (bounds[node].forward_upper, node))
+            .min()
+            .unwrap_or((0, 0));
+        progress.set_message(&format!(
+            "{} searches, diameter {}..={}, radius {}..={}",
+            searches,
+            diameter,
+            show_bound(diameter_upper),
+            radius_lower,
+            show_bound(radius_upper)
+        ));
+
+        let diameter_known = diameter == diameter_upper;
+        let radius_known = radius_lower == radius_upper;
+        if diameter_known && radius_known {
+            progress.finish_and_clear();
+            return Extremes {
+                diameter,
+                start,
+                end,
+                radius: radius_upper,
+                center: center as u32,
+                searches,
+            };
+        }
+
+        // Take turns in searching from the nodes that might be furthest from or to all others,
+        // and from the one that might be closest to all others. Nodes that were already searched
+        // from have exact bounds, so they are never picked again. Ties go to the node with the most
+        // links, which usually helps to narrow down the bounds of many others.
+        let pick_max = |bound: fn(&Bounds) -> usize| {
+            (0..n)
+                .into_par_iter()
+                .max_by_key(|&node| (bound(&bounds[node]), degrees[node], Reverse(node)))
+                .unwrap()
+        };
+        let source = match searches % 3 {
+            0 | 1 if diameter_known => pick_max(|b| usize::MAX - b.forward_lower),
+            0 => pick_max(|b| b.forward_upper),
+            1 => pick_max(|b| b.backward_upper),
+            _ if radius_known => pick_max(|b| b.forward_upper),
+            _ => pick_max(|b| usize::MAX - b.forward_lower),
+        } as u32;
+        searches += 1;
+
+        let ((from, forward), (to, backward)) = rayon::join(
+            || eccentricity(graph, source),
+            || eccentricity(reverse, source),
+        );
+        if forward.1 > diameter {
+            (diameter, start, end) = (forward.1, source, forward.0);
+        }
+        if backward.1 > diameter {
+            (diameter, start, end) = (backward.1, backward.0, source);
+        }
+
+        // d(v, u) <= d(v, source) + d(source, u) and the same the other way round.
+        bounds.par_iter_mut().enumerate().for_each(|(node, b)| {
+            let (from, to) = (from[node] as usize, to[node] as usize);
+            b.forward_lower = b.forward_lower.max(to).max(forward.1.saturating_sub(from));
+            b.forward_upper = b.forward_upper.min(to + forward.1);
+            b.backward_lower = b
+                .backward_lower
+                .max(from)
+                .max(backward.1.saturating_sub(to));
+            b.backward_upper = b.backward_upper.min(from + backward.1);
+        });
+    }
+}
+
+/// The distances from a node to all others, together with the furthest node and its distance.
+fn eccentricity(graph: &CompactGraph, source: u32) -> (Vec<u32>, (u32, usize)) {
+    let mut dist = vec![u32::MAX; graph.len()];
+    let mut order = Vec::new();
+    bfs(graph, source, &mut dist, &mut order);
+    assert_eq!(order.len(), graph.len(), "graph is not strongly connected");
+    let furthest = *order.last().unwrap();
+    let eccentricity = dist[furthest as usize] as usize;
+    (dist, (furthest, eccentricity))
+}
+
+fn show_bound(bound: usize) -> String {
+    if bound == usize::MAX {
+        "?".to_owned()
+    } else {
+        bound.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::{graphs, random_links};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// The distances between all pairs of nodes.
+    fn all_distances(graph: &CompactGraph) -> Vec<Vec<u32>> {
+        (0..graph.len() as u32)
+            .map(|node| eccentricity(graph, node).0)
+            .collect()
+    }
+
+    #[test]
+    fn diameter_and_radius_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for pages in [1, 2, 5, 20, 100, 300] {
+            for links in [0, pages / 2, pages, 3 * pages] {
+                // A cycle through all pages makes the graph strongly connected.
+                let mut all_links: Vec<_> = (0..pages).map(|p| (p, (p + 1) % pages)).collect();
+                all_links.retain(|(from, to)| from != to);
+                all_links.extend(random_links(&mut rng, pages, links));
+                let (graph, reverse) = graphs(&all_links);
+                let nodes: Vec<_> = (0..pages).collect();
+                let graph = CompactGraph::new(&graph, &nodes);
+                let reverse = CompactGraph::new(&reverse, &nodes);
+
+                let dist = all_distances(&graph);
+                let eccentricities: Vec<_> = dist
+                    .iter()
+                    .map(|d| *d.iter().max().unwrap() as usize)
+                    .collect();
+                let extremes = diameter_and_radius(&graph, &reverse);
+                assert_eq!(extremes.diameter, *eccentricities.iter().max().unwrap());
+                assert_eq!(extremes.radius, *eccentricities.iter().min().unwrap());
+                let (start, end) = (extremes.start as usize, extremes.end as usize);
+                assert_eq!(dist[start][end] as usize, extremes.diameter);
+                assert_eq!(eccentricities[extremes.center as usize], extremes.radius);
+                assert!(extremes.searches <= pages);
+            }
+        }
+    }
+}
@@ -1,45 +1,21 @@
 use flate2::read::GzDecoder;
-use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::{BufReader, BufWriter};
 use std::path::Path;
 
-macro_rules! _table_regex {
-    ($head:ident, $($tail:ident),+) => {
-        concat!(_table_regex!($head), "),(", _table_regex!($($tail),+))
-    };
-    (int) => {
-        "-?[0-9]+"
-    };
-    (uint) => {
-        "[0-9]+"
-    };
-    (string) => {
-        r"(?:'.*?')|(?:NULL)"
-    };
-    (float) => {
-        r"[0-9]+\.[0-9]+"
-    };
-}
-
-macro_rules! table_regex {
-    ($($rows:ident),+) => {
-        concat!("^(", _table_regex!($($rows),+), ")$")
-    };
-}
+use crate::sql::{self, Value};
+use crate::workspace::Workspace;
 
-const PAGE_REGEX: &str = table_regex!(
-    uint, int, string, string, int, int, float, string, string, uint, int, string, string
-);
-const REDIRECT_REGEX: &str = table_regex!(uint, int, string, string, string);
-const PAGELINKS_REGEX: &str = table_regex!(uint, int, string, int);
-
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq)]
 enum Table {
     Page,
     Pagelinks,
     Redirect,
+    Linktarget,
+    Categorylinks,
+    Langlinks,
 }
 
 impl Table {
@@ -48,6 +24,9 @@ impl Table {
             Table::Page => "page",
             Table::Pagelinks => "pagelinks",
             Table::Redirect => "redirect",
+            Table::Linktarget => "linktarget",
+            Table::Categorylinks => "categorylinks",
+            Table::Langlinks => "langlinks",
         }
     }
     fn target_file(self) -> &'static str {
@@ -55,109 +34,261 @@ impl Table {
             Table::Page => crate::PAGE_TABLE,
             Table::Pagelinks => crate::LINKS_TABLE,
             Table::Redirect => crate::REDIRECTS_TABLE,
+            Table::Linktarget => crate::LINKTARGET_TABLE,
+            Table::Categorylinks => crate::CATEGORYLINKS_TABLE,
+            Table::Langlinks => crate::LANGLINKS_TABLE,
         }
     }
-    fn regex(self) -> Regex {
-        Regex::new(match self {
-            Table::Page => PAGE_REGEX,
-            Table::Pagelinks => PAGELINKS_REGEX,
-            Table::Redirect => REDIRECT_REGEX,
-        })
-        .unwrap()
+    /// `linktarget` is only needed for dumps whose link tables refer to it,
+    /// `categorylinks` only for the category graph
+    /// and `langlinks` only to compare with other languages.
+    fn is_optional(self) -> bool {
+        match self {
+            Table::Linktarget | Table::Categorylinks | Table::Langlinks => true,
+            Table::Page | Table::Pagelinks | Table::Redirect => false,
+        }
     }
-    fn rows(self) -> Vec<usize> {
+    fn columns(self, linktarget: bool) -> &'static [&'static str] {
         match self {
-            Table::Page => vec![0, 2, 4],
-            Table::Pagelinks => vec![0, 2],
-            Table::Redirect => vec![0, 2],
+            Table::Page => &[
+                "page_id",
+                "page_namespace",
+                "page_title",
+                "page_is_redirect",
+            ],
+            Table::Pagelinks if linktarget => &["pl_from", "pl_target_id"],
+            Table::Pagelinks => &["pl_from", "pl_namespace", "pl_title"],
+            Table::Redirect => &["rd_from", "rd_namespace", "rd_title"],
+            Table::Linktarget => &["lt_id", "lt_namespace", "lt_title"],
+            Table::Categorylinks if linktarget => &["cl_from", "cl_target_id"],
+            Table::Categorylinks => &["cl_from", "cl_to"],
+            Table::Langlinks => &["ll_from", "ll_lang", "ll_title"],
         }
     }
-    fn namespace_rows(self) -> Vec<usize> {
+    fn namespace_columns(self, linktarget: bool) -> &'static [&'static str] {
         match self {
-            Table::Page => vec![1],
-            Table::Pagelinks => vec![1, 3],
-            Table::Redirect => vec![1],
+            Table::Page => &["page_namespace"],
+            Table::Pagelinks if linktarget => &["pl_from_namespace"],
+            Table::Pagelinks => &["pl_from_namespace", "pl_namespace"],
+            Table::Redirect => &["rd_namespace"],
+            Table::Linktarget => &["lt_namespace"],
+            Table::Categorylinks | Table::Langlinks => &[],
         }
     }
 }
 
-fn extract(table: Table, path: &Path) {
-    println!("Extracting table '{}' ...", table.name());
+/// Positions of the needed columns within the rows of a table dump.
+struct Layout {
+    columns: Vec<usize>,
+    namespace_columns: Vec<usize>,
+    width: usize,
+    linktarget: bool,
+}
+
+impl Layout {
+    fn new(table: Table, columns: &[String]) -> Result<Layout, String> {
+        // Since MediaWiki 1.43 `pagelinks` no longer stores the namespace and title
+        // of the link target but an ID into the `linktarget` table.
+        // `categorylinks` followed later. Both kept the old columns during the migration,
+        // when the new ones may still be `NULL`.
+        let has_column = |name: &str| columns.iter().any(|c| c == name);
+        let linktarget = match table {
+            Table::Pagelinks => !has_column("pl_title") && has_column("pl_target_id"),
+            Table::Categorylinks => !has_column("cl_to") && has_column("cl_target_id"),
+            _ => false,
+        };
+
+        let find = |names: &[&str]| {
+            names
+                .iter()
+                .map(|&name| {
+                    columns.iter().position(|c| c == name).ok_or_else(|| {
+                        format!(
+                            "Table '{}' is missing required column '{}'.",
+                            table.name(),
+                            name
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let selected = find(table.columns(linktarget))?;
+        let namespace_columns = find(table.namespace_columns(linktarget))?;
+
+        Ok(Layout {
+            width: columns.len(),
+            columns: selected,
+            namespace_columns,
+            linktarget,
+        })
+    }
+}
+
+fn extract(table: Table, path: &Path, ws: &Workspace, namespaces: &HashSet<i64>) -> Result<(), ()> {
+    eprintln!("Extracting table '{}' ...", table.name());
 
     let file = File::open(path).unwrap();
     let progress = crate::progress::progress_bar(file.metadata().unwrap().len());
     let decoder = GzDecoder::new(progress.wrap_read(file));
-    let reader = BufReader::new(decoder);
+    let mut parser = sql::Parser::new(BufReader::new(decoder));
 
-    let out_file = File::create(table.target_file()).unwrap();
+    let out_file = File::create(ws.path(table.target_file())).unwrap();
     let mut writer = BufWriter::new(out_file);
 
-    let line_start = format!("INSERT INTO `{}` VALUES ", table.name());
-    let regex = table.regex();
-    let rows = table.rows();
-    let namespace_rows = table.namespace_rows();
+    let mut layout = None;
+    let mut link_targets = None;
 
-    for line in reader.lines() {
-        if let Ok(line) = line {
-            if line.starts_with(&line_start) {
-                let mut iter = line.split(" VALUES ");
-                let values = iter.nth(1).unwrap();
-                let iter = values[1..values.len() - 2].split("),("); // Problem: (362495,0,'Seesterne_(Klasse),(Art),(Gattung)',101)
-                for val in iter {
-                    let val = val.replace("\\xe2\\x80\\x93", "-");
-                    let captures = regex.captures(&val);
-                    let captures = match captures {
-                        Some(c) => c,
-                        None => {
-                            println!("Failed to parse: '{}'", val);
-                            continue;
-                        }
-                    };
-                    if namespace_rows.iter().any(|i| &captures[i + 1] != "0") {
-                        continue;
+    loop {
+        let values = match parser.next_row() {
+            Ok(Some(values)) => values,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("Failed to parse table '{}': {}", table.name(), e);
+                return Err(());
+            }
+        };
+        if parser.table() != table.name() {
+            continue;
+        }
+        let layout = match &layout {
+            Some(layout) => layout,
+            None => {
+                let columns = match parser.columns(table.name()) {
+                    Some(columns) => columns,
+                    None => {
+                        eprintln!(
+                            "Missing CREATE TABLE statement for table '{}'.",
+                            table.name()
+                        );
+                        return Err(());
                     }
-                    for &i in &rows {
-                        if i == 0 {
-                            write!(writer, "{}", &captures[i + 1]).unwrap();
-                        } else {
-                            write!(writer, "\t{}", &captures[i + 1]).unwrap();
-                        }
+                };
+                let new_layout = match Layout::new(table, columns) {
+                    Ok(layout) => layout,
+                    Err(e) => {
+                        eprintln!("{}", e);
+                        return Err(());
                     }
-                    writeln!(writer).unwrap();
+                };
+                if new_layout.linktarget && !crate::file_exists(ws.path(crate::LINKTARGET_TABLE)) {
+                    eprintln!("This '{}' dump refers to link targets by ID.", table.name());
+                    eprintln!("Please also download the 'linktarget' table and try again.");
+                    return Err(());
                 }
+                if new_layout.linktarget {
+                    link_targets = Some(match table {
+                        Table::Categorylinks => {
+                            let categories = [crate::generator::CATEGORY_NAMESPACE];
+                            load_link_targets(ws, &categories.iter().copied().collect())
+                        }
+                        _ => load_link_targets(ws, namespaces),
+                    });
+                }
+                layout.get_or_insert(new_layout)
+            }
+        };
+        if values.len() != layout.width {
+            eprintln!("Failed to parse: {:?}", values);
+            continue;
+        }
+        let in_namespaces = |i: &usize| match values[*i] {
+            Value::Int(namespace) => namespaces.contains(&namespace),
+            _ => false,
+        };
+        if !layout.namespace_columns.iter().all(in_namespaces) {
+            continue;
+        }
+        if let Some(link_targets) = &link_targets {
+            let target = match values[layout.columns[1]] {
+                Value::Int(target_id) => link_targets.get(&target_id),
+                _ => None,
+            };
+            let from = &values[layout.columns[0]];
+            match target {
+                Some((_, title)) if table == Table::Categorylinks => {
+                    writeln!(writer, "{}\t{}", from, title).unwrap();
+                }
+                Some((namespace, title)) => {
+                    writeln!(writer, "{}\t{}\t{}", from, namespace, title).unwrap();
+                }
+                None => (),
             }
-        } else {
-            println!("Skipped line with invalid UTF-8");
+            continue;
+        }
+        for (n, &i) in layout.columns.iter().enumerate() {
+            if n == 0 {
+                write!(writer, "{}", values[i]).unwrap();
+            } else {
+                write!(writer, "\t{}", values[i]).unwrap();
+            }
+        }
+        writeln!(writer).unwrap();
+    }
+
+    progress.finish();
+    Ok(())
+}
+
+/// The namespaces and titles of the link targets in the given namespaces
+/// from the already extracted `linktarget` table.
+///
+/// Link targets are resolved during extraction so the extracted `pagelinks`
+/// and `categorylinks` tables always refer to pages by title, whatever the schema of the dump.
+fn load_link_targets(ws: &Workspace, namespaces: &HashSet<i64>) -> HashMap<i64, (i64, String)> {
+    let file = File::open(ws.path(crate::LINKTARGET_TABLE)).unwrap();
+    let reader = BufReader::new(file);
+    let mut titles = HashMap::new();
+
+    for line in reader.lines() {
+        let line = line.unwrap();
+        let mut iter = line.trim().split('\t');
+        let target_id = iter.next().unwrap().parse().unwrap();
+        let namespace: i64 = iter.next().unwrap().parse().unwrap();
+        if namespaces.contains(&namespace) {
+            titles.insert(target_id, (namespace, iter.next().unwrap().to_owned()));
         }
     }
+
+    titles
 }
 
-pub fn ensure_extracted() -> Result<(), ()> {
-    for table in &[Table::Page, Table::Redirect, Table::Pagelinks] {
-        if !crate::file_exists(table.target_file()) {
-            let name = format!("-{}.sql.gz", table.name());
-            let data_dir = std::fs::read_dir("data").unwrap();
-            let mut files = data_dir.filter_map(|entry| {
-                let entry = entry.unwrap();
-                if entry.file_name().into_string().unwrap().ends_with(&name) {
-                    Some(entry.path())
-                } else {
-                    None
-                }
-            });
+pub fn ensure_extracted(ws: &Workspace, namespaces: &HashSet<i64>) -> Result<(), ()> {
+    // Category pages are needed as nodes of the category graph
+    // even if they are not part of the link graph.
+    let mut with_categories = namespaces.clone();
+    if crate::file_exists(ws.path(crate::CATEGORYLINKS_TABLE))
+        || ws.dump(Table::Categorylinks.name()).is_some()
+    {
+        with_categories.insert(crate::generator::CATEGORY_NAMESPACE);
+    }
 
-            if let Some(file) = files.next() {
-                if files.next().is_some() {
-                    println!("Multiple table dumps for table '{}'.", table.name());
-                    println!("Please move or delete the others and try again.");
+    for table in &[
+        Table::Page,
+        Table::Redirect,
+        Table::Linktarget,
+        Table::Pagelinks,
+        Table::Categorylinks,
+        Table::Langlinks,
+    ] {
+        if !crate::file_exists(ws.path(table.target_file())) {
+            let namespaces = if *table == Table::Pagelinks {
+                namespaces
+            } else {
+                &with_categories
+            };
+            if let Some(file) = ws.dump(table.name()) {
+                if extract(*table, &file, ws, namespaces).is_err() {
+                    std::fs::remove_file(ws.path(table.target_file())).unwrap();
                     return Err(());
                 }
-                extract(*table, &file);
-            } else {
-                println!("Missing database dumps.");
-                println!("Please downloade the 3 tables 'page', 'pagelinks' and 'redirects'");
-                println!("from https://dumps.wikimedia.org/ as .sql.gz files,");
-                println!("place them in the 'data' directory and try again.");
+            } else if !table.is_optional() {
+                eprintln!(
+                    "Missing dump of table '{}' for '{}'.",
+                    table.name(),
+                    ws.name
+                );
+                eprintln!("Please download it from https://dumps.wikimedia.org/ as .sql.gz file,");
+                eprintln!("place it in the data directory and try again.");
                 return Err(());
             }
         }
@@ -2,58 +2,89 @@ use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{prelude::*, BufReader, BufWriter};
 
+use crate::workspace::Workspace;
+
+pub const CATEGORY_NAMESPACE: i64 = 14;
+
+/// A page title together with its namespace, as titles are only unique within a namespace.
+type Title = (i64, String);
+
 #[derive(Default)]
 struct GraphBuilder {
     graph: HashMap<usize, HashSet<usize>>,
     reverse_graph: HashMap<usize, HashSet<usize>>,
+    namespaces: HashSet<i64>,
     page_ids: HashSet<usize>,
-    title_to_id: HashMap<String, usize>,
-    redirect_from_id: HashMap<usize, String>,
-    redirects: HashMap<String, usize>,
+    category_ids: HashSet<usize>,
+    categories: HashMap<usize, HashSet<usize>>,
+    reverse_categories: HashMap<usize, HashSet<usize>>,
+    title_to_id: HashMap<Title, usize>,
+    redirect_from_id: HashMap<usize, Title>,
+    redirects: HashMap<Title, usize>,
 }
 
-pub fn generate() {
+pub fn generate(ws: &Workspace, namespaces: &HashSet<i64>) {
     let _progress = crate::progress::msg("Generating link graph");
 
-    let mut builder = GraphBuilder::default();
-    builder.load_and_preprocess_pages();
-    builder.load_redirects();
-    builder.build_graph();
+    let mut builder = GraphBuilder {
+        namespaces: namespaces.clone(),
+        ..GraphBuilder::default()
+    };
+    builder.load_and_preprocess_pages(ws);
+    builder.load_redirects(ws);
+    builder.build_graph(ws);
 
-    let writer = BufWriter::new(File::create(crate::GRAPH).unwrap());
+    let writer = BufWriter::new(File::create(ws.path(crate::GRAPH)).unwrap());
     bincode::serialize_into(writer, &builder.graph).unwrap();
 
-    let writer = BufWriter::new(File::create(crate::GRAPH_REVERSE).unwrap());
+    let writer = BufWriter::new(File::create(ws.path(crate::GRAPH_REVERSE)).unwrap());
     bincode::serialize_into(writer, &builder.reverse_graph).unwrap();
+
+    if crate::file_exists(ws.path(crate::CATEGORYLINKS_TABLE)) {
+        builder.build_category_graph(ws);
+
+        let writer = BufWriter::new(File::create(ws.path(crate::CATEGORIES)).unwrap());
+        bincode::serialize_into(writer, &builder.categories).unwrap();
+
+        let writer = BufWriter::new(File::create(ws.path(crate::CATEGORIES_REVERSE)).unwrap());
+        bincode::serialize_into(writer, &builder.reverse_categories).unwrap();
+    }
+
+    ws.save_namespaces(namespaces);
 }
 
 impl GraphBuilder {
-    fn load_and_preprocess_pages(&mut self) {
-        let file = File::open(crate::PAGE_TABLE).unwrap();
+    fn load_and_preprocess_pages(&mut self, ws: &Workspace) {
+        let file = File::open(ws.path(crate::PAGE_TABLE)).unwrap();
         let reader = BufReader::new(file);
-        let mut writer = BufWriter::new(File::create(crate::PAGES).unwrap());
+        let mut writer = BufWriter::new(File::create(ws.path(crate::PAGES)).unwrap());
 
         for line in reader.lines() {
             let line = line.unwrap();
             let mut iter = line.trim().split('\t');
             let page_id: usize = iter.next().unwrap().parse().unwrap();
 
-            let title = iter.next().unwrap();
+            let title = parse_title(&mut iter);
             let is_redirect = iter.next().unwrap();
 
             if is_redirect == "0" {
-                let title_nice = cleanup_name(&title[1..title.len() - 1]);
-                writeln!(writer, "{}\t{}", page_id, title_nice).unwrap();
-                self.page_ids.insert(page_id);
-                self.title_to_id.insert(title.to_owned(), page_id);
+                let title_nice = title.1.replace('_', " ");
+                writeln!(writer, "{}\t{}\t{}", page_id, title.0, title_nice).unwrap();
+                if self.namespaces.contains(&title.0) {
+                    self.page_ids.insert(page_id);
+                }
+                if title.0 == CATEGORY_NAMESPACE {
+                    self.category_ids.insert(page_id);
+                }
+                self.title_to_id.insert(title, page_id);
             } else {
-                self.redirect_from_id.insert(page_id, title.to_owned());
+                self.redirect_from_id.insert(page_id, title);
             }
         }
     }
 
-    fn load_redirects(&mut self) {
-        let file = File::open(crate::REDIRECTS_TABLE).unwrap();
+    fn load_redirects(&mut self, ws: &Workspace) {
+        let file = File::open(ws.path(crate::REDIRECTS_TABLE)).unwrap();
         let reader = BufReader::new(file);
 
         for line in reader.lines() {
@@ -61,75 +92,77 @@ impl GraphBuilder {
             let mut iter = line.trim().split('\t');
             let page_id: usize = iter.next().unwrap().parse().unwrap();
 
-            let to_title = iter.next().unwrap();
-            if !self.title_to_id.contains_key(to_title) {
+            let to_title = parse_title(&mut iter);
+            if let Some(&to_id) = self.title_to_id.get(&to_title) {
+                if let Some(title) = self.redirect_from_id.get(&page_id) {
+                    self.redirects.insert(title.clone(), to_id);
+                }
+            }
+        }
+    }
+
+    fn build_graph(&mut self, ws: &Workspace) {
+        let file = File::open(ws.path(crate::LINKS_TABLE)).unwrap();
+        let reader = BufReader::new(file);
+
+        for line in reader.lines() {
+            let line = line.unwrap();
+            let mut iter = line.trim().split('\t');
+            let from_id: usize = iter.next().unwrap().parse().unwrap();
+            if !self.page_ids.contains(&from_id) {
                 continue;
             }
-            if let Some(title) = self.redirect_from_id.get(&page_id) {
-                if let Some(&to_id) = self.title_to_id.get(title) {
-                    self.redirects.insert(title.to_owned(), to_id);
+            if let Some(to_id) = self.resolve(&parse_title(&mut iter)) {
+                if self.page_ids.contains(&to_id) {
+                    self.add_edge(from_id, to_id);
                 }
             }
         }
     }
 
-    fn build_graph(&mut self) {
-        let file = File::open(crate::LINKS_TABLE).unwrap();
+    /// Builds the graph from pages and subcategories to the categories they are in.
+    fn build_category_graph(&mut self, ws: &Workspace) {
+        let file = File::open(ws.path(crate::CATEGORYLINKS_TABLE)).unwrap();
         let reader = BufReader::new(file);
 
         for line in reader.lines() {
             let line = line.unwrap();
             let mut iter = line.trim().split('\t');
             let from_id: usize = iter.next().unwrap().parse().unwrap();
-            let to_title = iter.next().unwrap();
-
-            if !self.page_ids.contains(&from_id) {
+            if !self.page_ids.contains(&from_id) && !self.category_ids.contains(&from_id) {
                 continue;
             }
-            if let Some(&to_id) = self.title_to_id.get(to_title) {
-                self.add_edge(from_id, to_id);
-            } else if let Some(&to_id) = self.redirects.get(to_title) {
-                self.add_edge(from_id, to_id);
+            let category = (CATEGORY_NAMESPACE, iter.next().unwrap().to_owned());
+            if let Some(category_id) = self.resolve(&category) {
+                if category_id != from_id && self.category_ids.contains(&category_id) {
+                    self.categories
+                        .entry(from_id)
+                        .or_default()
+                        .insert(category_id);
+                    self.reverse_categories
+                        .entry(category_id)
+                        .or_default()
+                        .insert(from_id);
+                }
             }
         }
     }
 
+    fn resolve(&self, title: &Title) -> Option<usize> {
+        self.title_to_id
+            .get(title)
+            .or_else(|| self.redirects.get(title))
+            .copied()
+    }
+
     fn add_edge(&mut self, from: usize, to: usize) {
         self.graph.entry(from).or_default().insert(to);
         self.reverse_graph.entry(to).or_default().insert(from);
     }
 }
 
-fn cleanup_name(s: &str) -> String {
-    let mut result = Vec::new();
-    let mut iter = s.chars();
-    let mut buf = [0u8; 4];
-
-    while let Some(c) = iter.next() {
-        if c == '_' {
-            result.push(b' ');
-        } else if c != '\\' {
-            result.extend(c.encode_utf8(&mut buf).bytes())
-        } else {
-            match iter.next().unwrap() {
-                'b' => result.push(8),
-                'f' => result.push(12),
-                'n' => result.push(b'\n'),
-                'r' => result.push(b'\r'),
-                't' => result.push(b'\t'),
-                '\\' => result.push(b'\\'),
-                '\'' => result.push(b'\''),
-                '"' => result.push(b'"'),
-                'x' => {
-                    let mut s = String::new();
-                    s.push(iter.next().unwrap());
-                    s.push(iter.next().unwrap());
-                    result.push(u8::from_str_radix(&s, 16).unwrap());
-                }
-                c => panic!("unimplemented escape char: '{}'", c),
-            }
-        }
-    }
-
-    String::from_utf8(result).unwrap()
+fn parse_title<'a>(iter: &mut impl Iterator<Item = &'a str>) -> Title {
+    let namespace = iter.next().unwrap().parse().unwrap();
+    let title = iter.next().unwrap();
+    (namespace, title.to_owned())
 }
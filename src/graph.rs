@@ -1,22 +1,40 @@
-use rand::Rng;
-use std::collections::{HashMap, HashSet, VecDeque};
+use rand::{seq::SliceRandom, Rng};
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::io::BufReader;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::workspace::Workspace;
 
 pub struct Graph {
     pub graph: HashMap<usize, HashSet<usize>>,
 }
 
+/// Why no path between two pages was found.
+#[derive(Debug, Clone, Copy)]
+pub enum PathError {
+    /// The page is not part of the graph, i.e. it neither has links nor is linked to.
+    UnknownPage(usize),
+    /// The target can't be reached from the start.
+    NoPath,
+}
+
 impl Graph {
-    pub fn load(reverse: bool) -> Graph {
-        let graph_file = if reverse {
-            println!("Loading reverse graph ..");
-            crate::GRAPH_REVERSE
+    pub fn load(workspace: &Workspace, reverse: bool) -> Graph {
+        if reverse {
+            eprintln!("Loading reverse graph ..");
+            Graph::load_file(workspace.path(crate::GRAPH_REVERSE))
         } else {
-            println!("Loading graph ..");
-            crate::GRAPH
-        };
-        let graph_file = File::open(graph_file).unwrap();
+            eprintln!("Loading graph ..");
+            Graph::load_file(workspace.path(crate::GRAPH))
+        }
+    }
+
+    pub fn load_file(path: impl AsRef<Path>) -> Graph {
+        let graph_file = File::open(path).unwrap();
         let progress = crate::progress::progress_bar(&graph_file);
         let reader = BufReader::new(progress.wrap_read(graph_file));
         let graph = bincode::deserialize_from(reader).unwrap();
@@ -25,43 +43,325 @@ impl Graph {
         Graph { graph }
     }
 
-    pub fn find_shortest_path(&self, start: usize, end: usize) -> Vec<usize> {
-        let mut visited = HashSet::new();
+    /// Finds a shortest path with a bidirectional BFS that always expands the smaller frontier.
+    /// `reverse` must be the reverse graph of this one.
+    pub fn find_shortest_path(
+        &self,
+        reverse: &Graph,
+        start: usize,
+        end: usize,
+    ) -> Result<Vec<usize>, PathError> {
+        self.find_shortest_path_with(reverse, start, end, |_, _| true)
+    }
+
+    /// Like `find_shortest_path`, but only follows the links for which `allowed(from, to)` is true.
+    pub fn find_shortest_path_with(
+        &self,
+        reverse: &Graph,
+        start: usize,
+        end: usize,
+        allowed: impl Fn(usize, usize) -> bool,
+    ) -> Result<Vec<usize>, PathError> {
+        if start == end {
+            return Ok(vec![start]);
+        }
+        self.check_known(reverse, start)?;
+        self.check_known(reverse, end)?;
+
+        // The previous page on the way from the start and the next page on the way to the end.
+        let mut prev = HashMap::new();
+        let mut next = HashMap::new();
+        let mut forward = vec![start];
+        let mut backward = vec![end];
+        prev.insert(start, start);
+        next.insert(end, end);
+
+        let meet = loop {
+            if forward.is_empty() || backward.is_empty() {
+                return Err(PathError::NoPath);
+            }
+            let meet = if forward.len() <= backward.len() {
+                self.expand(&mut forward, &mut prev, &next, &allowed)
+            } else {
+                reverse.expand(&mut backward, &mut next, &prev, &|to, from| {
+                    allowed(from, to)
+                })
+            };
+            if let Some(meet) = meet {
+                break meet;
+            }
+        };
+
+        let mut path = vec![meet];
+        let mut curr = meet;
+        while curr != start {
+            curr = prev[&curr];
+            path.push(curr);
+        }
+        path.reverse();
+        curr = meet;
+        while curr != end {
+            curr = next[&curr];
+            path.push(curr);
+        }
+        Ok(path)
+    }
+
+    /// Fails if a page is not part of the graph at all, i.e. it neither has links nor is linked to.
+    fn check_known(&self, reverse: &Graph, page: usize) -> Result<(), PathError> {
+        if self.graph.contains_key(&page) || reverse.graph.contains_key(&page) {
+            Ok(())
+        } else {
+            Err(PathError::UnknownPage(page))
+        }
+    }
+
+    /// Finds a shortest path that passes the `via` pages in order and doesn't enter any of the
+    /// `avoid` pages. Pages may be visited more than once on the way between two waypoints.
+    pub fn find_constrained_path(
+        &self,
+        reverse: &Graph,
+        start: usize,
+        end: usize,
+        via: &[usize],
+        avoid: &HashSet<usize>,
+    ) -> Result<Vec<usize>, PathError> {
+        let mut stops = vec![start];
+        stops.extend(via);
+        stops.push(end);
+
+        let mut path = vec![start];
+        for leg in stops.windows(2) {
+            let leg = self
+                .find_shortest_path_with(reverse, leg[0], leg[1], |_, to| !avoid.contains(&to))?;
+            path.extend(&leg[1..]);
+        }
+        Ok(path)
+    }
+
+    /// Finds the cheapest path with Dijkstra's algorithm, where following a link costs more
+    /// the more links the target page has. Returns the path and its total cost.
+    pub fn find_weighted_path(
+        &self,
+        reverse: &Graph,
+        start: usize,
+        end: usize,
+        degree: Degree,
+        cost: Cost,
+    ) -> Result<(Vec<usize>, f64), PathError> {
+        let degree_of = |page| {
+            let graph = match degree {
+                Degree::In => reverse,
+                Degree::Out => self,
+            };
+            graph.graph.get(&page).map_or(0, HashSet::len)
+        };
+        if start == end {
+            return Ok((vec![start], 0.0));
+        }
+        self.check_known(reverse, start)?;
+        self.check_known(reverse, end)?;
+        let mut dist = HashMap::new();
         let mut came_from = HashMap::new();
-        let mut todo = VecDeque::new();
+        let mut todo = BinaryHeap::new();
+        dist.insert(start, 0.0);
+        todo.push(Visit {
+            cost: 0.0,
+            page: start,
+        });
 
-        todo.push_back(start);
-        visited.insert(start);
+        while let Some(Visit {
+            cost: c,
+            page: curr,
+        }) = todo.pop()
+        {
+            if curr == end {
+                let mut path = vec![end];
+                let mut curr = end;
+                while curr != start {
+                    curr = came_from[&curr];
+                    path.push(curr);
+                }
+                path.reverse();
+                return Ok((path, c));
+            }
+            if c > dist[&curr] {
+                continue;
+            }
+            for &n in self.graph.get(&curr).into_iter().flatten() {
+                let next = c + cost.of(degree_of(n));
+                if dist.get(&n).is_none_or(|&d| next < d) {
+                    dist.insert(n, next);
+                    came_from.insert(n, curr);
+                    todo.push(Visit {
+                        cost: next,
+                        page: n,
+                    });
+                }
+            }
+        }
 
-        while let Some(curr) = todo.pop_front() {
-            if let Some(neigbhors) = self.graph.get(&curr) {
-                for &n in neigbhors {
-                    if !visited.contains(&n) {
-                        todo.push_back(n);
-                        visited.insert(n);
-                        came_from.insert(n, curr);
+        Err(PathError::NoPath)
+    }
 
-                        if n == end {
-                            todo.clear();
-                            break;
-                        }
+    /// Finds the `k` shortest paths from `start` to `end` that don't visit any page twice
+    /// using Yen's algorithm, ordered by length.
+    pub fn k_shortest_paths(
+        &self,
+        reverse: &Graph,
+        start: usize,
+        end: usize,
+        k: usize,
+    ) -> Result<Vec<Vec<usize>>, PathError> {
+        let first = self.find_shortest_path(reverse, start, end)?;
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let mut paths = vec![first];
+        // Sorted by length first, so the shortest candidate is always taken next.
+        let mut candidates = BTreeSet::new();
+
+        while paths.len() < k {
+            let last = paths.last().unwrap();
+
+            // Branch off the last path at every page, without reusing a link that
+            // one of the found paths with the same beginning already takes there.
+            for i in 0..last.len() - 1 {
+                let root = &last[..=i];
+                let blocked_pages: HashSet<_> = root[..i].iter().copied().collect();
+                let blocked_links: HashSet<_> = paths
+                    .iter()
+                    .filter(|path| path.len() > i + 1 && path[..=i] == *root)
+                    .map(|path| (path[i], path[i + 1]))
+                    .collect();
+
+                let spur = self.find_shortest_path_with(reverse, last[i], end, |from, to| {
+                    !blocked_pages.contains(&to) && !blocked_links.contains(&(from, to))
+                });
+                if let Ok(spur) = spur {
+                    let mut path = root[..i].to_vec();
+                    path.extend(spur);
+                    candidates.insert((path.len(), path));
+                }
+            }
+
+            match candidates.pop_first() {
+                Some((_, path)) => paths.push(path),
+                None => break,
+            }
+        }
+
+        Ok(paths)
+    }
+
+    /// Finds all shortest paths from `start` to `end`.
+    pub fn all_shortest_paths(
+        &self,
+        reverse: &Graph,
+        start: usize,
+        end: usize,
+    ) -> Result<ShortestPaths, PathError> {
+        if start != end {
+            self.check_known(reverse, start)?;
+            self.check_known(reverse, end)?;
+        }
+        // Distances from the start, known for all pages closer than the end.
+        let mut dist = HashMap::new();
+        let mut todo = VecDeque::new();
+        dist.insert(start, 0);
+        todo.push_back(start);
+
+        'search: while let Some(curr) = todo.pop_front() {
+            if curr == end {
+                break;
+            }
+            let d = dist[&curr];
+            for &n in self.graph.get(&curr).into_iter().flatten() {
+                if let Entry::Vacant(e) = dist.entry(n) {
+                    e.insert(d + 1);
+                    todo.push_back(n);
+                    if n == end {
+                        break 'search;
                     }
                 }
             }
         }
+        let length = *dist.get(&end).ok_or(PathError::NoPath)?;
 
-        let mut path = vec![end];
-        let mut curr = end;
+        // Walk back from the end, only following links that go one step closer to the start.
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut order = vec![end];
+        next.insert(end, Vec::new());
+        let mut i = 0;
+        while i < order.len() {
+            let curr = order[i];
+            let d = dist[&curr];
+            for &n in reverse.graph.get(&curr).into_iter().flatten() {
+                if d > 0 && dist.get(&n) == Some(&(d - 1)) {
+                    let new = !next.contains_key(&n);
+                    next.entry(n).or_default().push(curr);
+                    if new {
+                        order.push(n);
+                    }
+                }
+            }
+            i += 1;
+        }
 
-        while let Some(&from) = came_from.get(&curr) {
-            path.push(from);
-            curr = from;
+        // `order` is sorted by the distance to the end, so all successors are counted first.
+        let mut count = HashMap::new();
+        for &n in &order {
+            let successors = next.get_mut(&n).unwrap();
+            successors.sort_unstable();
+            let c = if n == end {
+                1
+            } else {
+                successors
+                    .iter()
+                    .fold(0u128, |sum, s| sum.saturating_add(count[s]))
+            };
+            count.insert(n, c);
         }
 
-        path.reverse();
-        path
+        Ok(ShortestPaths {
+            start,
+            end,
+            length,
+            next,
+            count,
+        })
     }
 
+    /// Expands a BFS frontier by one level and returns the first new node
+    /// that was already reached by the search from the other side.
+    fn expand(
+        &self,
+        frontier: &mut Vec<usize>,
+        came_from: &mut HashMap<usize, usize>,
+        other: &HashMap<usize, usize>,
+        allowed: &dyn Fn(usize, usize) -> bool,
+    ) -> Option<usize> {
+        let mut next = Vec::new();
+        for &curr in frontier.iter() {
+            for &n in self.graph.get(&curr).into_iter().flatten() {
+                if !allowed(curr, n) {
+                    continue;
+                }
+                if let Entry::Vacant(e) = came_from.entry(n) {
+                    e.insert(curr);
+                    if other.contains_key(&n) {
+                        return Some(n);
+                    }
+                    next.push(n);
+                }
+            }
+        }
+        *frontier = next;
+        None
+    }
+
+    /// The page furthest from `start` and its distance. Of several pages at the same distance,
+    /// the one with the smallest ID is picked, so the result doesn't depend on the hash order.
     pub fn find_furthest(&self, start: usize) -> (usize, usize) {
         let mut visited = HashSet::new();
         let mut todo = VecDeque::new();
@@ -77,7 +377,7 @@ impl Graph {
             if count % 10_000 == 0 {
                 progress.set_position(count as u64);
             }
-            if dist > max_dist {
+            if dist > max_dist || (dist == max_dist && curr < max_node) {
                 max_dist = dist;
                 max_node = curr;
             }
@@ -95,8 +395,38 @@ impl Graph {
         (max_node, max_dist)
     }
 
-    pub fn estimate_diameter(&self) -> (usize, usize, usize) {
-        let mut curr = self.random_node();
+    /// The distances of all pages reachable from `start`, including `start` itself.
+    pub fn distances(&self, start: usize) -> HashMap<usize, usize> {
+        let mut dist = HashMap::new();
+        let mut todo = VecDeque::new();
+        let progress = crate::progress::progress_bar(self.graph.len());
+
+        dist.insert(start, 0);
+        todo.push_back(start);
+
+        while let Some(curr) = todo.pop_front() {
+            let count = dist.len() - todo.len();
+            if count % 10_000 == 0 {
+                progress.set_position(count as u64);
+            }
+            let d = dist[&curr] + 1;
+            for &n in self.graph.get(&curr).into_iter().flatten() {
+                if let Entry::Vacant(e) = dist.entry(n) {
+                    e.insert(d);
+                    todo.push_back(n);
+                }
+            }
+        }
+
+        progress.finish();
+        dist
+    }
+
+    /// Finds a lower bound for the diameter by repeatedly going to the furthest page,
+    /// starting at a random one.
+    /// Returns `None` if no page has links.
+    pub fn estimate_diameter(&self, rng: &mut impl Rng) -> Option<(usize, usize, usize)> {
+        let mut curr = self.random_node(rng)?;
         let mut max_start = curr;
         let mut max_end = curr;
         let mut max_dist = 0;
@@ -115,16 +445,421 @@ impl Graph {
             curr = next;
         }
 
-        (max_start, max_end, max_dist)
+        Some((max_start, max_end, max_dist))
+    }
+
+    /// Picks one of the pages with links uniformly at random, or `None` if there are none.
+    /// The pages are sorted first, so the pick only depends on the random numbers.
+    fn random_node(&self, rng: &mut impl Rng) -> Option<usize> {
+        let mut pages: Vec<_> = self.graph.keys().copied().collect();
+        pages.sort_unstable();
+        pages.choose(rng).copied()
     }
+}
+
+/// A graph with the pages numbered from 0 and all links in one array,
+/// which is much smaller and faster to go through than a `Graph`.
+pub struct CompactGraph {
+    /// The page ID of each node, sorted.
+    pub pages: Vec<usize>,
+    /// The links of node `i` are `targets[offsets[i]..offsets[i + 1]]`.
+    offsets: Vec<usize>,
+    targets: Vec<u32>,
+}
 
-    fn random_node(&self) -> usize {
-        let max = self.graph.len();
-        loop {
-            let rnd = rand::thread_rng().gen_range(0, max);
-            if self.graph.contains_key(&rnd) {
-                return rnd;
+impl CompactGraph {
+    /// Builds the graph of the given pages, which must be sorted.
+    /// Links to pages that are not in the list are left out.
+    pub fn new(graph: &Graph, pages: &[usize]) -> CompactGraph {
+        let mut compact = CompactGraph {
+            pages: pages.to_vec(),
+            offsets: Vec::with_capacity(pages.len() + 1),
+            targets: Vec::new(),
+        };
+        compact.offsets.push(0);
+        for page in pages {
+            for &n in graph.graph.get(page).into_iter().flatten() {
+                if let Some(n) = compact.node(n) {
+                    compact.targets.push(n);
+                }
             }
+            let start = compact.offsets.last().copied().unwrap();
+            compact.targets[start..].sort_unstable();
+            compact.offsets.push(compact.targets.len());
         }
+        compact
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// The node of a page, if it is part of the graph.
+    pub fn node(&self, page: usize) -> Option<u32> {
+        self.pages.binary_search(&page).ok().map(|i| i as u32)
+    }
+
+    pub fn links(&self, node: u32) -> &[u32] {
+        &self.targets[self.offsets[node as usize]..self.offsets[node as usize + 1]]
+    }
+}
+
+/// Which links of a page make it expensive to visit in a weighted path.
+#[derive(Clone, Copy)]
+pub enum Degree {
+    In,
+    Out,
+}
+
+impl FromStr for Degree {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Degree, ()> {
+        match s {
+            "in" => Ok(Degree::In),
+            "out" => Ok(Degree::Out),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How the cost of visiting a page grows with its degree.
+#[derive(Clone, Copy)]
+pub enum Cost {
+    Log,
+    Sqrt,
+    Linear,
+}
+
+impl Cost {
+    /// The cost of following a link to a page with the given degree, which is at least 1.
+    pub fn of(self, degree: usize) -> f64 {
+        let degree = degree as f64;
+        match self {
+            Cost::Log => 1.0 + degree.ln_1p(),
+            Cost::Sqrt => 1.0 + degree.sqrt(),
+            Cost::Linear => 1.0 + degree,
+        }
+    }
+}
+
+impl FromStr for Cost {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Cost, ()> {
+        match s {
+            "log" => Ok(Cost::Log),
+            "sqrt" => Ok(Cost::Sqrt),
+            "linear" => Ok(Cost::Linear),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A page in the queue of Dijkstra's algorithm, ordered so that the cheapest one comes first.
+struct Visit {
+    cost: f64,
+    page: usize,
+}
+
+impl PartialEq for Visit {
+    fn eq(&self, other: &Visit) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Visit {}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Visit) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Visit) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.page.cmp(&self.page))
+    }
+}
+
+/// All shortest paths between two pages, stored as a DAG.
+pub struct ShortestPaths {
+    start: usize,
+    end: usize,
+    /// The number of links on each of the paths.
+    pub length: usize,
+    /// The successors of all pages on the paths, sorted by ID.
+    next: HashMap<usize, Vec<usize>>,
+    /// The number of paths from each page to the end, saturating at `u128::MAX`.
+    count: HashMap<usize, u128>,
+}
+
+impl ShortestPaths {
+    /// The number of distinct shortest paths, saturating at `u128::MAX`.
+    pub fn count(&self) -> u128 {
+        self.count[&self.start]
+    }
+
+    /// Lists up to `limit` paths in lexicographic order of their page IDs.
+    pub fn list(&self, limit: usize) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        let mut path = vec![self.start];
+        // The index of the next successor to try for each page on the path.
+        let mut indices = vec![0];
+        if self.start == self.end && limit > 0 {
+            paths.push(path.clone());
+        }
+
+        while paths.len() < limit {
+            let curr = *path.last().unwrap();
+            let i = indices.last_mut().unwrap();
+            match self.next[&curr].get(*i) {
+                Some(&n) => {
+                    *i += 1;
+                    path.push(n);
+                    indices.push(0);
+                    if n == self.end {
+                        paths.push(path.clone());
+                    }
+                }
+                None => {
+                    path.pop();
+                    indices.pop();
+                    if path.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        paths
+    }
+
+    /// Picks one of the paths uniformly at random.
+    ///
+    /// If the number of paths saturated `u128::MAX`, the result is only approximately uniform.
+    pub fn sample(&self, rng: &mut impl Rng) -> Vec<usize> {
+        let mut path = vec![self.start];
+        let mut curr = self.start;
+
+        while curr != self.end {
+            let mut r = rng.gen_range(0, self.count[&curr]);
+            for &n in &self.next[&curr] {
+                if r < self.count[&n] {
+                    curr = n;
+                    break;
+                }
+                r -= self.count[&n];
+            }
+            path.push(curr);
+        }
+
+        path
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// The graph and its reverse from a list of links.
+    pub(crate) fn graphs(links: &[(usize, usize)]) -> (Graph, Graph) {
+        let mut graph = Graph {
+            graph: HashMap::new(),
+        };
+        let mut reverse = Graph {
+            graph: HashMap::new(),
+        };
+        for &(from, to) in links {
+            graph.graph.entry(from).or_default().insert(to);
+            reverse.graph.entry(to).or_default().insert(from);
+        }
+        (graph, reverse)
+    }
+
+    pub(crate) fn random_links(
+        rng: &mut StdRng,
+        pages: usize,
+        links: usize,
+    ) -> Vec<(usize, usize)> {
+        (0..links)
+            .map(|_| (rng.gen_range(0, pages), rng.gen_range(0, pages)))
+            .filter(|(from, to)| from != to)
+            .collect()
+    }
+
+    /// All paths from `curr` to `end` that don't visit a page twice.
+    fn simple_paths(graph: &Graph, path: &mut Vec<usize>, end: usize, paths: &mut Vec<Vec<usize>>) {
+        let curr = *path.last().unwrap();
+        if curr == end {
+            paths.push(path.clone());
+            return;
+        }
+        for &n in graph.graph.get(&curr).into_iter().flatten() {
+            if !path.contains(&n) {
+                path.push(n);
+                simple_paths(graph, path, end, paths);
+                path.pop();
+            }
+        }
+    }
+
+    #[test]
+    fn k_shortest_paths_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..200 {
+            let (graph, reverse) = graphs(&random_links(&mut rng, 8, 20));
+            let (start, end) = (0, 7);
+            let mut all = Vec::new();
+            simple_paths(&graph, &mut vec![start], end, &mut all);
+            let mut lengths: Vec<_> = all.iter().map(Vec::len).collect();
+            lengths.sort_unstable();
+
+            match graph.k_shortest_paths(&reverse, start, end, 6) {
+                Ok(paths) => {
+                    let found: Vec<_> = paths.iter().map(Vec::len).collect();
+                    assert_eq!(found, lengths[..lengths.len().min(6)]);
+                    for (i, path) in paths.iter().enumerate() {
+                        assert!(all.contains(path), "not a simple path: {:?}", path);
+                        assert!(!paths[..i].contains(path), "found twice: {:?}", path);
+                    }
+                }
+                Err(_) => assert!(all.is_empty()),
+            }
+        }
+    }
+
+    #[test]
+    fn k_shortest_paths_in_a_grid() {
+        // 0 1 2
+        // 3 4 5
+        // 6 7 8
+        let mut links = Vec::new();
+        for i in 0..9 {
+            if i % 3 < 2 {
+                links.push((i, i + 1));
+            }
+            if i < 6 {
+                links.push((i, i + 3));
+            }
+        }
+        let (graph, reverse) = graphs(&links);
+        let paths = graph.k_shortest_paths(&reverse, 0, 8, 10).unwrap();
+        // There are exactly 6 monotone paths, all with 4 steps.
+        assert_eq!(paths.len(), 6);
+        assert!(paths.iter().all(|path| path.len() == 5));
+    }
+
+    #[test]
+    fn all_shortest_paths_match_brute_force() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..100 {
+            let (graph, reverse) = graphs(&random_links(&mut rng, 8, 20));
+            for (start, end) in (0..8).flat_map(|start| (0..8).map(move |end| (start, end))) {
+                let mut all = Vec::new();
+                simple_paths(&graph, &mut vec![start], end, &mut all);
+                let shortest = all.iter().map(Vec::len).min();
+                all.retain(|path| Some(path.len()) == shortest);
+                all.sort_unstable();
+
+                match graph.all_shortest_paths(&reverse, start, end) {
+                    Ok(paths) => {
+                        assert_eq!(paths.length + 1, shortest.unwrap());
+                        assert_eq!(paths.count(), all.len() as u128);
+                        assert_eq!(paths.list(usize::MAX), all);
+                        assert_eq!(paths.list(2), all[..all.len().min(2)]);
+                        assert!(paths.list(0).is_empty());
+                        assert!(all.contains(&paths.sample(&mut rng)));
+                    }
+                    Err(_) => assert!(all.is_empty()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn shortest_paths_are_sampled_uniformly() {
+        // A 4 by 4 grid with links to the right and down, so there are 20 paths
+        // from the top left to the bottom right corner.
+        let mut links = Vec::new();
+        for i in 0..16 {
+            if i % 4 < 3 {
+                links.push((i, i + 1));
+            }
+            if i < 12 {
+                links.push((i, i + 4));
+            }
+        }
+        let (graph, reverse) = graphs(&links);
+        let paths = graph.all_shortest_paths(&reverse, 0, 15).unwrap();
+        let all = paths.list(usize::MAX);
+        assert_eq!(paths.count(), 20);
+        assert_eq!(all.len(), 20);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut counts = HashMap::new();
+        for _ in 0..20_000 {
+            *counts.entry(paths.sample(&mut rng)).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 20);
+        // Each path is expected 1000 times, with a standard deviation of about 31.
+        assert!(counts.values().all(|&c| (850..1150).contains(&c)));
+    }
+
+    #[test]
+    fn k_shortest_paths_without_path() {
+        let (graph, reverse) = graphs(&[(0, 1), (2, 1)]);
+        assert!(matches!(
+            graph.k_shortest_paths(&reverse, 0, 2, 3),
+            Err(PathError::NoPath)
+        ));
+        assert!(matches!(
+            graph.k_shortest_paths(&reverse, 0, 5, 3),
+            Err(PathError::UnknownPage(5))
+        ));
+    }
+
+    #[test]
+    fn furthest_page_is_the_smallest_at_the_largest_distance() {
+        let (graph, _) = graphs(&[(0, 9), (0, 4), (9, 7), (4, 8), (4, 5), (9, 6)]);
+        assert_eq!(graph.find_furthest(0), (5, 2));
+    }
+
+    #[test]
+    fn diameter_estimate_is_reproducible() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let links = random_links(&mut rng, 300, 900);
+        // Every graph hashes with different keys, so the links are visited in another order.
+        let estimates: Vec<_> = (0..5)
+            .map(|_| {
+                graphs(&links)
+                    .0
+                    .estimate_diameter(&mut StdRng::seed_from_u64(1))
+            })
+            .collect();
+        assert!(estimates.iter().all(|&e| e == estimates[0]));
+    }
+
+    #[test]
+    fn diameter_estimate_with_sparse_ids() {
+        // Far more IDs than pages, as in a wiki where most pages are in other namespaces.
+        let (graph, _) = graphs(&[(11, 12), (12, 13), (1_000_000, 11)]);
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut starts = HashSet::new();
+        for _ in 0..100 {
+            starts.insert(graph.random_node(&mut rng).unwrap());
+            let (_, _, dist) = graph.estimate_diameter(&mut rng).unwrap();
+            assert!(dist >= 1);
+        }
+        // All pages with links can be picked, not only those with IDs below the page count.
+        let expected: HashSet<_> = [11, 12, 1_000_000].iter().copied().collect();
+        assert_eq!(starts, expected);
+
+        let (empty, _) = graphs(&[]);
+        assert_eq!(empty.estimate_diameter(&mut rng), None);
     }
 }
@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::CompactGraph;
+use crate::rank::MAX_ITERATIONS;
+use crate::workspace::Workspace;
+
+/// The scores count as converged once the hubs and authorities of all pages
+/// together change by less than this in one round.
+const TOLERANCE: f64 = 1e-8;
+
+/// The hub and authority scores of all pages of a `CompactGraph`, in the order of its nodes.
+/// Good hubs link to many good authorities, and good authorities are linked from many good hubs.
+#[derive(Serialize, Deserialize)]
+pub struct Hits {
+    pub iterations: usize,
+    pub hubs: Vec<f64>,
+    pub authorities: Vec<f64>,
+}
+
+impl Hits {
+    pub fn compute(graph: &CompactGraph, reverse: &CompactGraph) -> Hits {
+        let _progress = crate::progress::msg("Computing hub and authority scores");
+        let n = graph.len();
+        let mut hubs = vec![1.0 / (n as f64).sqrt(); n];
+        let mut authorities = vec![0.0; n];
+        let mut iterations = 0;
+
+        while iterations < MAX_ITERATIONS {
+            iterations += 1;
+            let next_authorities = normalized(step(reverse, &hubs));
+            let next_hubs = normalized(step(graph, &next_authorities));
+            let change =
+                difference(&next_hubs, &hubs) + difference(&next_authorities, &authorities);
+            hubs = next_hubs;
+            authorities = next_authorities;
+            if change < TOLERANCE {
+                break;
+            }
+        }
+
+        Hits {
+            iterations,
+            hubs,
+            authorities,
+        }
+    }
+
+    pub fn load(workspace: &Workspace) -> Hits {
+        let _progress = crate::progress::msg("Loading hub and authority scores");
+        let file = File::open(workspace.path(crate::HITS)).unwrap();
+        bincode::deserialize_from(BufReader::new(file)).unwrap()
+    }
+
+    pub fn save(&self, workspace: &Workspace) {
+        let writer = BufWriter::new(File::create(workspace.path(crate::HITS)).unwrap());
+        bincode::serialize_into(writer, self).unwrap();
+    }
+}
+
+/// Sums up the scores of the linked nodes for every node.
+fn step(graph: &CompactGraph, scores: &[f64]) -> Vec<f64> {
+    (0..graph.len() as u32)
+        .into_par_iter()
+        .map(|node| {
+            // `sum` of no values is -0.0, which would be printed with a sign.
+            graph
+                .links(node)
+                .iter()
+                .fold(0.0, |sum, &link| sum + scores[link as usize])
+        })
+        .collect()
+}
+
+/// Scales the scores to a euclidean length of 1, unless they are all 0.
+fn normalized(mut scores: Vec<f64>) -> Vec<f64> {
+    let norm = scores
+        .par_iter()
+        .map(|score| score * score)
+        .sum::<f64>()
+        .sqrt();
+    if norm > 0.0 {
+        scores.par_iter_mut().for_each(|score| *score /= norm);
+    }
+    scores
+}
+
+fn difference(a: &[f64], b: &[f64]) -> f64 {
+    a.par_iter().zip(b).map(|(a, b)| (a - b).abs()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::tests::graphs;
+
+    #[test]
+    fn pages_without_links_score_positive_zero() {
+        let (graph, reverse) = graphs(&[(0, 1), (0, 2), (3, 1)]);
+        let pages: Vec<_> = (0..5).collect();
+        let hits = Hits::compute(
+            &CompactGraph::new(&graph, &pages),
+            &CompactGraph::new(&reverse, &pages),
+        );
+        // Page 4 has no links at all, 1 and 2 only incoming ones and 3 only an outgoing one.
+        for score in [
+            hits.hubs[1],
+            hits.hubs[2],
+            hits.hubs[4],
+            hits.authorities[3],
+        ] {
+            assert_eq!(score.to_bits(), 0.0f64.to_bits());
+        }
+        assert!(hits.hubs[0] > hits.hubs[3]);
+        assert!(hits.authorities[1] > hits.authorities[2]);
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{prelude::*, BufReader};
+
+use crate::graph::Graph;
+use crate::workspace::Workspace;
+
+/// The generated data of another wiki, e.g. of another language.
+pub struct OtherWiki {
+    pub name: String,
+    pub lang: String,
+    pub title_to_id: HashMap<String, usize>,
+    pub id_to_title: HashMap<usize, String>,
+    pub graph: Graph,
+    pub reverse_graph: Graph,
+}
+
+impl OtherWiki {
+    pub fn load(workspace: Workspace, namespaces: &HashSet<i64>) -> Result<OtherWiki, ()> {
+        let pages = workspace.path(crate::PAGES);
+        let graph = workspace.path(crate::GRAPH);
+        let reverse_graph = workspace.path(crate::GRAPH_REVERSE);
+        if !pages.exists() || !graph.exists() || !reverse_graph.exists() {
+            eprintln!("No generated link graph found for '{}'.", workspace.name);
+            eprintln!("Run the analyzer for the other wiki first.");
+            return Err(());
+        }
+        if workspace.namespaces() != *namespaces {
+            eprintln!(
+                "The link graph of '{}' was generated for the namespaces {}.",
+                workspace.name,
+                crate::workspace::namespace_list(&workspace.namespaces())
+            );
+            eprintln!("Run the analyzer for it with the same '--namespaces' first.");
+            return Err(());
+        }
+
+        let (title_to_id, id_to_title, _) = crate::load_pages(pages, namespaces);
+        eprintln!("Loading graph of '{}' ..", workspace.name);
+        let graph = Graph::load_file(graph);
+        let reverse_graph = Graph::load_file(reverse_graph);
+
+        Ok(OtherWiki {
+            lang: workspace.lang(),
+            name: workspace.name,
+            title_to_id,
+            id_to_title,
+            graph,
+            reverse_graph,
+        })
+    }
+}
+
+/// Links between the articles of this wiki and their counterparts in one other language.
+///
+/// Only articles (namespace 0) are linked: the titles of the counterparts carry the namespace
+/// prefixes of the other language, e.g. "Kategorie:", which the dumps don't translate.
+pub struct Langlinks {
+    to_other: HashMap<usize, String>,
+    from_other: HashMap<String, usize>,
+}
+
+impl Langlinks {
+    pub fn load(
+        workspace: &Workspace,
+        lang: &str,
+        id_to_title: &HashMap<usize, String>,
+    ) -> Langlinks {
+        let _progress = crate::progress::msg("Loading language links");
+        let mut to_other = HashMap::new();
+        let mut from_other = HashMap::new();
+        let articles = articles(workspace);
+
+        let reader = BufReader::new(File::open(workspace.path(crate::LANGLINKS_TABLE)).unwrap());
+        for line in reader.lines() {
+            let line = line.unwrap();
+            let mut iter = line.trim().split('\t');
+            let page_id: usize = iter.next().unwrap().parse().unwrap();
+
+            if iter.next().unwrap() != lang
+                || !id_to_title.contains_key(&page_id)
+                || !articles.contains(&page_id)
+            {
+                continue;
+            }
+            if let Some(title) = iter.next() {
+                to_other.insert(page_id, title.to_owned());
+                from_other.insert(title.to_owned(), page_id);
+            }
+        }
+
+        Langlinks {
+            to_other,
+            from_other,
+        }
+    }
+
+    /// The title of the counterpart of a page in the other language.
+    pub fn counterpart(&self, page: usize) -> Option<&str> {
+        self.to_other.get(&page).map(String::as_str)
+    }
+
+    /// The page in this wiki a title in the other language corresponds to.
+    pub fn page_for(&self, other_title: &str) -> Option<usize> {
+        self.from_other.get(other_title).copied()
+    }
+}
+
+/// The IDs of all pages in namespace 0.
+fn articles(workspace: &Workspace) -> HashSet<usize> {
+    let reader = BufReader::new(File::open(workspace.path(crate::PAGES)).unwrap());
+    reader
+        .lines()
+        .filter_map(|line| {
+            let line = line.unwrap();
+            let mut iter = line.split('\t');
+            let id = iter.next().unwrap().parse().unwrap();
+            (iter.next() == Some("0")).then_some(id)
+        })
+        .collect()
+}
@@ -1,38 +1,113 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{prelude::*, BufReader};
+use std::io::{prelude::*, BufReader, BufWriter};
 use std::path::Path;
 
+mod bowtie;
+mod categories;
+mod centrality;
+mod cli;
+mod components;
+mod diameter;
 mod extractor;
 mod generator;
 mod graph;
+mod hits;
+mod langlinks;
+mod output;
 mod progress;
+mod rank;
+mod separation;
+mod sql;
+mod workspace;
 
-use graph::Graph;
+use bowtie::Region;
+use categories::Categories;
+use cli::{CmdResult, Error, Input, Options};
+use components::Components;
+use graph::{CompactGraph, Cost, Degree, Graph};
+use hits::Hits;
+use langlinks::{Langlinks, OtherWiki};
+use output::{Format, Page};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use rank::PageRank;
+use regex::Regex;
+use workspace::Workspace;
 
-const PAGE_TABLE: &str = "data/page.csv";
-const REDIRECTS_TABLE: &str = "data/redirect.csv";
-const LINKS_TABLE: &str = "data/pagelinks.csv";
+const DATA_DIR: &str = "data";
 
-pub const PAGES: &str = "data/pages.csv";
-pub const GRAPH: &str = "data/graph.bin";
-pub const GRAPH_REVERSE: &str = "data/graph_reverse.bin";
+const PAGE_TABLE: &str = "page.csv";
+const REDIRECTS_TABLE: &str = "redirect.csv";
+const LINKS_TABLE: &str = "pagelinks.csv";
+const LINKTARGET_TABLE: &str = "linktarget.csv";
+const CATEGORYLINKS_TABLE: &str = "categorylinks.csv";
+const LANGLINKS_TABLE: &str = "langlinks.csv";
+
+/// How many shortest paths the `paths` command lists by default.
+const DEFAULT_PATH_LIMIT: usize = 20;
+
+/// How many components besides the largest one the `components` command lists by default.
+const DEFAULT_COMPONENT_LIMIT: usize = 10;
+
+/// How many pages ranking commands list by default.
+const DEFAULT_TOP: usize = 10;
+
+/// How many random pages sampled estimates like the betweenness start from by default.
+const DEFAULT_CENTRALITY_SAMPLES: usize = 100;
+
+/// How many paths the `kpaths` command finds by default.
+const DEFAULT_K_PATHS: usize = 5;
+
+pub const PAGES: &str = "pages.csv";
+pub const GRAPH: &str = "graph.bin";
+pub const GRAPH_REVERSE: &str = "graph_reverse.bin";
+pub const CATEGORIES: &str = "categories.bin";
+pub const CATEGORIES_REVERSE: &str = "categories_reverse.bin";
+pub const COMPONENTS: &str = "components.bin";
+pub const PAGERANK: &str = "pagerank.bin";
+pub const HITS: &str = "hits.bin";
+/// The namespaces the files in the workspace were generated for.
+pub const NAMESPACES: &str = "namespaces.txt";
+
+/// How many pages are shown as a sample of a larger group.
+const SAMPLE_SIZE: usize = 5;
 
 struct Main<'a> {
+    options: Options,
+    workspace: Workspace,
     title_to_id: HashMap<String, usize>,
     id_to_title: HashMap<usize, String>,
+    /// All pages in the link graph, including the ones without any links, sorted by ID.
+    graph_pages: Vec<usize>,
     graph: Option<Graph>,
     reverse_graph: Option<Graph>,
+    compact_graph: Option<CompactGraph>,
+    compact_reverse_graph: Option<CompactGraph>,
+    components: Option<Components>,
+    pagerank: Option<PageRank>,
+    hits: Option<Hits>,
+    categories: Option<Categories>,
+    other_wiki: Option<(OtherWiki, Langlinks)>,
     stdin_lock: std::io::StdinLock<'a>,
 }
 
 impl Main<'_> {
-    fn new() -> Self {
+    fn new(options: Options, workspace: Workspace) -> Self {
         Main {
+            options,
+            workspace,
             title_to_id: HashMap::new(),
             id_to_title: HashMap::new(),
+            graph_pages: Vec::new(),
             graph: None,
             reverse_graph: None,
+            compact_graph: None,
+            compact_reverse_graph: None,
+            components: None,
+            pagerank: None,
+            hits: None,
+            categories: None,
+            other_wiki: None,
             stdin_lock: Box::leak(Box::new(std::io::stdin())).lock(),
         }
     }
@@ -44,51 +119,220 @@ impl Main<'_> {
         result
     }
 
-    fn read_page(&mut self, prompt: &str) -> usize {
+    /// Asks for a page until a valid one is entered. Fails at the end of the input.
+    fn read_page(&mut self, prompt: &str) -> Result<usize, Error> {
         print!("{} ", prompt);
         loop {
             let title = self.read_line();
+            if title.is_empty() {
+                return Err(Error::Failed);
+            }
             let title = title.trim();
             if let Some(&id) = self.title_to_id.get(title) {
-                return id;
+                return Ok(id);
             }
             print!("Invalid page. Try again: ");
         }
     }
 
-    fn load(&mut self) -> Result<(), ()> {
-        if !file_exists(PAGES) || !file_exists(GRAPH) || !file_exists(GRAPH_REVERSE) {
-            extractor::ensure_extracted()?;
-            generator::generate();
+    fn lookup(&self, title: &str) -> Result<usize, Error> {
+        match self.title_to_id.get(title.trim()) {
+            Some(&id) => Ok(id),
+            None => {
+                eprintln!("Unknown page '{}'.", title);
+                Err(Error::Failed)
+            }
         }
+    }
 
-        let _progress = progress::msg("Loading pages");
+    /// Takes a page from the arguments or asks for it in interactive mode.
+    fn page(&mut self, input: &mut Input, prompt: &str) -> Result<usize, Error> {
+        match input.next()? {
+            Some(title) => self.lookup(&title),
+            None if input.interactive => self.read_page(prompt),
+            None => Err(missing_argument(prompt)),
+        }
+    }
 
-        let reader = BufReader::new(File::open(PAGES).unwrap());
-        for line in reader.lines() {
-            let line = line.unwrap();
-            let mut iter = line.split('\t');
-            let id: usize = iter.next().unwrap().parse().unwrap();
-            let title = iter.next().unwrap();
-            self.id_to_title.insert(id, title.to_owned());
-            self.title_to_id.insert(title.to_owned(), id);
+    /// Takes a text argument or asks for it in interactive mode.
+    fn text(&mut self, input: &mut Input, prompt: &str) -> Result<String, Error> {
+        match input.next()? {
+            Some(text) => Ok(text),
+            None if input.interactive => {
+                print!("{} ", prompt);
+                match self.read_line() {
+                    line if line.is_empty() => Err(Error::Failed),
+                    line => Ok(line.trim().to_owned()),
+                }
+            }
+            None => Err(missing_argument(prompt)),
         }
+    }
+
+    fn load(&mut self) -> Result<(), ()> {
+        let ws = &self.workspace;
+        let namespaces = &self.options.namespaces;
+        if file_exists(ws.path(PAGES)) && ws.namespaces() != *namespaces {
+            let generated = workspace::namespace_list(&ws.namespaces());
+            eprintln!(
+                "The link graph of '{}' was generated for the namespaces {}.",
+                ws.name, generated
+            );
+            if ["page", "pagelinks", "redirect"]
+                .iter()
+                .any(|table| ws.dump(table).is_none())
+            {
+                eprintln!(
+                    "The dumps to generate it again are missing, use '--namespaces {}'.",
+                    generated
+                );
+                return Err(());
+            }
+            eprintln!(
+                "Generating it again for the namespaces {}.",
+                workspace::namespace_list(namespaces)
+            );
+            remove_generated_files(ws);
+        }
+        if !file_exists(ws.path(PAGES))
+            || !file_exists(ws.path(GRAPH))
+            || !file_exists(ws.path(GRAPH_REVERSE))
+        {
+            extractor::ensure_extracted(ws, &self.options.namespaces)?;
+            generator::generate(ws, &self.options.namespaces);
+        }
+
+        let (title_to_id, id_to_title, graph_pages) =
+            load_pages(ws.path(PAGES), &self.options.namespaces);
+        self.title_to_id = title_to_id;
+        self.id_to_title = id_to_title;
+        self.graph_pages = graph_pages;
 
         Ok(())
     }
 
     fn ensure_graph(&mut self) {
         if self.graph.is_none() {
-            self.graph = Some(Graph::load(false));
+            self.graph = Some(Graph::load(&self.workspace, false));
         }
     }
 
     fn ensure_reverse_graph(&mut self) {
         if self.reverse_graph.is_none() {
-            self.reverse_graph = Some(Graph::load(true));
+            self.reverse_graph = Some(Graph::load(&self.workspace, true));
+        }
+    }
+
+    fn ensure_compact_graph(&mut self) {
+        if self.compact_graph.is_none() {
+            self.ensure_graph();
+            let _progress = progress::msg("Building compact graph");
+            self.compact_graph = Some(CompactGraph::new(self.graph(), &self.graph_pages));
         }
     }
 
+    fn ensure_compact_reverse_graph(&mut self) {
+        if self.compact_reverse_graph.is_none() {
+            self.ensure_reverse_graph();
+            let _progress = progress::msg("Building compact reverse graph");
+            let graph = CompactGraph::new(self.reverse_graph(), &self.graph_pages);
+            self.compact_reverse_graph = Some(graph);
+        }
+    }
+
+    fn ensure_components(&mut self) {
+        if self.components.is_some() {
+            return;
+        }
+        // Saved results for a graph with a different number of pages are outdated.
+        if file_exists(self.workspace.path(COMPONENTS)) {
+            let components = Components::load(&self.workspace);
+            if components.pages() == self.graph_pages.len() {
+                self.components = Some(components);
+                return;
+            }
+        }
+        self.ensure_compact_graph();
+        let components = Components::compute(self.compact_graph());
+        components.save(&self.workspace);
+        self.components = Some(components);
+    }
+
+    /// Loads the PageRank computed with the given parameters, or computes and saves it.
+    fn ensure_pagerank(&mut self, params: rank::Params) {
+        if self
+            .pagerank
+            .as_ref()
+            .is_some_and(|rank| rank.params == params)
+        {
+            return;
+        }
+        if file_exists(self.workspace.path(PAGERANK)) {
+            let pagerank = PageRank::load(&self.workspace);
+            if pagerank.params == params && pagerank.scores.len() == self.graph_pages.len() {
+                self.pagerank = Some(pagerank);
+                return;
+            }
+        }
+        self.ensure_compact_graph();
+        self.ensure_compact_reverse_graph();
+        let pagerank =
+            PageRank::compute(self.compact_graph(), self.compact_reverse_graph(), params);
+        eprintln!("PageRank took {} iterations.", pagerank.iterations);
+        pagerank.save(&self.workspace);
+        self.pagerank = Some(pagerank);
+    }
+
+    fn ensure_hits(&mut self) {
+        if self.hits.is_some() {
+            return;
+        }
+        if file_exists(self.workspace.path(HITS)) {
+            let hits = Hits::load(&self.workspace);
+            if hits.hubs.len() == self.graph_pages.len() {
+                self.hits = Some(hits);
+                return;
+            }
+        }
+        self.ensure_compact_graph();
+        self.ensure_compact_reverse_graph();
+        let hits = Hits::compute(self.compact_graph(), self.compact_reverse_graph());
+        eprintln!("HITS took {} iterations.", hits.iterations);
+        hits.save(&self.workspace);
+        self.hits = Some(hits);
+    }
+
+    fn ensure_categories(&mut self) -> CmdResult {
+        if self.categories.is_none() {
+            if !file_exists(self.workspace.path(CATEGORIES)) {
+                eprintln!("No category graph available.");
+                eprintln!("Please download the 'categorylinks' table, delete the generated");
+                eprintln!(
+                    "files in '{}' and try again.",
+                    self.workspace.path("").display()
+                );
+                return Err(Error::Failed);
+            }
+            self.categories = Some(Categories::load(&self.workspace));
+        }
+        Ok(())
+    }
+
+    fn ensure_other_wiki(&mut self, wiki: &str) -> CmdResult {
+        let workspace =
+            Workspace::select(&self.options.data_dir, Some(wiki)).map_err(|()| Error::Failed)?;
+        if let Some((other, _)) = &self.other_wiki {
+            if other.name == workspace.name {
+                return Ok(());
+            }
+        }
+        let other =
+            OtherWiki::load(workspace, &self.options.namespaces).map_err(|()| Error::Failed)?;
+        let langlinks = Langlinks::load(&self.workspace, &other.lang, &self.id_to_title);
+        self.other_wiki = Some((other, langlinks));
+        Ok(())
+    }
+
     fn graph(&self) -> &Graph {
         self.graph.as_ref().unwrap()
     }
@@ -97,90 +341,1164 @@ impl Main<'_> {
         self.reverse_graph.as_ref().unwrap()
     }
 
-    fn run(&mut self) {
-        if self.load().is_err() {
-            return;
+    fn compact_graph(&self) -> &CompactGraph {
+        self.compact_graph.as_ref().unwrap()
+    }
+
+    fn compact_reverse_graph(&self) -> &CompactGraph {
+        self.compact_reverse_graph.as_ref().unwrap()
+    }
+
+    /// The node of a page in the compact graph, which must be loaded.
+    fn node(&self, page: usize) -> Result<u32, Error> {
+        self.compact_graph()
+            .node(page)
+            .ok_or_else(|| self.not_in_graph(page))
+    }
+
+    /// Fails if the page is outside the namespaces of the link graph.
+    fn check_in_graph(&self, page: usize) -> CmdResult {
+        match self.graph_pages.binary_search(&page) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(self.not_in_graph(page)),
         }
+    }
 
-        loop {
-            print!("What do you want to do? ");
-            match self.read_line().trim() {
-                "help" => {
-                    println!("links     - List all the links on a page");
-                    println!("path      - Find the shortest path from one page to another");
-                    println!("furthest  - Find the page furthest away from a starting point");
-                    println!("max       - Find the maximal number of steps needed to get to a page from anywhere");
-                    println!("diameter  - Approximate the diameter of the link graph (i.e. how far the furthest two pages are apart)");
-                    println!("exit");
+    fn not_in_graph(&self, page: usize) -> Error {
+        eprintln!(
+            "'{}' is not part of the link graph.",
+            self.id_to_title[&page]
+        );
+        Error::Failed
+    }
+
+    fn components(&self) -> &Components {
+        self.components.as_ref().unwrap()
+    }
+
+    fn categories(&self) -> &Categories {
+        self.categories.as_ref().unwrap()
+    }
+
+    fn compare_paths(&self, start: usize, end: usize, format: Format) -> CmdResult {
+        let (other, langlinks) = self.other_wiki.as_ref().unwrap();
+        let counterpart = |page| {
+            let other_page = langlinks
+                .counterpart(page)
+                .and_then(|title| other.title_to_id.get(title).copied());
+            if other_page.is_none() {
+                eprintln!(
+                    "'{}' is not an article or has no counterpart in the '{}' wiki.",
+                    self.id_to_title[&page], other.lang
+                );
+            }
+            other_page
+        };
+        let (other_start, other_end) = match (counterpart(start), counterpart(end)) {
+            (Some(other_start), Some(other_end)) => (other_start, other_end),
+            _ => return Err(Error::Failed),
+        };
+
+        let result = self
+            .graph()
+            .find_shortest_path(self.reverse_graph(), start, end);
+        let other_result =
+            other
+                .graph
+                .find_shortest_path(&other.reverse_graph, other_start, other_end);
+        let path = result.as_deref().unwrap_or_default();
+        let other_path = other_result.as_deref().unwrap_or_default();
+        let other_path_mapped: Vec<_> = other_path
+            .iter()
+            .map(|n| langlinks.page_for(&other.id_to_title[n]))
+            .collect();
+
+        let mut comparison = output::Comparison {
+            lang: &other.lang,
+            outcome: output::Outcome::new(&result, &self.id_to_title),
+            other_outcome: output::Outcome::new(&other_result, &other.id_to_title),
+            path: Page::list(path, &self.id_to_title),
+            other_path: other_path
+                .iter()
+                .zip(&other_path_mapped)
+                .map(|(&n, mapped)| output::OtherPage {
+                    page: Page::new(n, &other.id_to_title),
+                    counterpart: mapped.map(|mapped| Page::new(mapped, &self.id_to_title)),
+                })
+                .collect(),
+            difference: None,
+            identical: false,
+            diverge_after: None,
+            shared: None,
+        };
+
+        if !path.is_empty() && !other_path.is_empty() {
+            comparison.difference = Some(other_path.len() as i64 - path.len() as i64);
+
+            let diverge = path
+                .iter()
+                .zip(&other_path_mapped)
+                .position(|(n, mapped)| Some(*n) != *mapped);
+            match diverge {
+                None if path.len() == other_path.len() => comparison.identical = true,
+                None | Some(0) => (),
+                Some(i) => {
+                    comparison.diverge_after = Some(Page::new(path[i - 1], &self.id_to_title))
                 }
-                "links" => {
-                    self.ensure_graph();
-                    let page = self.read_page("Page:");
-                    let links = &self.graph().graph[&page];
-                    println!("{} links:", links.len());
-                    for link in links {
-                        println!("{}", self.id_to_title[link]);
-                    }
+            }
+
+            let intermediate = |len: usize| 1..len.max(2) - 1;
+            let on_path: HashSet<_> = path[intermediate(path.len())].iter().collect();
+            let shared = other_path_mapped[intermediate(other_path.len())]
+                .iter()
+                .filter(|mapped| mapped.is_some_and(|n| on_path.contains(&n)))
+                .count();
+            comparison.shared = Some(shared);
+        }
+
+        output::print(&comparison, format);
+        Ok(())
+    }
+
+    /// The output format, either given with `--format` or the default one.
+    fn format(&self, input: &mut Input) -> Result<Format, Error> {
+        Ok(input.parsed("format")?.unwrap_or(self.options.format))
+    }
+
+    fn execute(&mut self, command: &str, input: &mut Input) -> CmdResult {
+        match command {
+            "help" => {
+                input.finish()?;
+                print_commands();
+                Ok(())
+            }
+            "links" => self.links(input),
+            "path" => self.path(input),
+            "wpath" => self.weighted_path(input),
+            "paths" => self.all_paths(input),
+            "kpaths" => self.k_shortest_paths(input),
+            "furthest" => self.furthest(input),
+            "max" => self.max(input),
+            "distances" => self.distances(input),
+            "diameter" => self.diameter(input),
+            "components" => self.components_summary(input),
+            "component" => self.component(input),
+            "samecomponent" => self.same_component(input),
+            "bowtie" => self.bow_tie(input),
+            "rank" => self.rank(input),
+            "related" => self.related(input),
+            "hubs" => self.hits_command(input, false),
+            "authorities" => self.hits_command(input, true),
+            "betweenness" => self.betweenness(input),
+            "closeness" => self.closeness(input),
+            "separation" => self.separation(input),
+            "categories" => self.categories_command(input),
+            "topcategories" => self.top_categories(input),
+            "catpath" => self.category_path(input),
+            "compare" => self.compare(input),
+            _ => Err(Error::Usage(format!("Unknown command '{}'", command))),
+        }
+    }
+
+    fn links(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let page = self.page(input, "Page:")?;
+        input.finish()?;
+        self.ensure_graph();
+
+        let mut links: Vec<_> = match self.graph().graph.get(&page) {
+            Some(links) => links
+                .iter()
+                .map(|&link| Page::new(link, &self.id_to_title))
+                .collect(),
+            None => Vec::new(),
+        };
+        links.sort_by_key(|link| link.title);
+        let links = output::Links {
+            page: Page::new(page, &self.id_to_title),
+            links,
+        };
+        output::print(&links, format);
+        Ok(())
+    }
+
+    fn path(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let avoid_titles = input.values("avoid")?;
+        let avoid_patterns = input.values("avoid-regex")?;
+        let via_titles = input.values("via")?;
+        let start = self.page(input, "Start page:")?;
+        let end = self.page(input, "Target page:")?;
+        input.finish()?;
+
+        let mut avoid = HashSet::new();
+        for title in &avoid_titles {
+            avoid.insert(self.lookup(title)?);
+        }
+        for pattern in &avoid_patterns {
+            let regex = Regex::new(pattern)
+                .map_err(|e| Error::Usage(format!("Invalid regex '{}': {}", pattern, e)))?;
+            let matching = self.id_to_title.iter().filter(|(_, t)| regex.is_match(t));
+            avoid.extend(matching.map(|(&id, _)| id));
+        }
+        let via = via_titles
+            .iter()
+            .map(|title| self.lookup(title))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Pages that were asked for explicitly are never avoided.
+        for page in via.iter().chain(&[start, end]) {
+            avoid.remove(page);
+        }
+        self.ensure_graph();
+        self.ensure_reverse_graph();
+
+        let path =
+            self.graph()
+                .find_constrained_path(self.reverse_graph(), start, end, &via, &avoid);
+        let path = output::Path::new(start, end, &path, &self.id_to_title);
+        output::print(&path, format);
+        path_found(&path.outcome)
+    }
+
+    fn weighted_path(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let degree = input.parsed("degree")?.unwrap_or(Degree::In);
+        let cost = input.parsed("cost")?.unwrap_or(Cost::Log);
+        let start = self.page(input, "Start page:")?;
+        let end = self.page(input, "Target page:")?;
+        input.finish()?;
+        self.ensure_graph();
+        self.ensure_reverse_graph();
+
+        let weighted =
+            self.graph()
+                .find_weighted_path(self.reverse_graph(), start, end, degree, cost);
+        let outcome = output::Outcome::new(&weighted, &self.id_to_title);
+        let (path, cost) = match weighted {
+            Ok((path, cost)) => (path, Some(cost)),
+            Err(_) => (Vec::new(), None),
+        };
+        let report = output::WeightedPath {
+            start: Page::new(start, &self.id_to_title),
+            end: Page::new(end, &self.id_to_title),
+            outcome,
+            steps: path.len().checked_sub(1),
+            cost,
+            path: Page::list(&path, &self.id_to_title),
+        };
+        output::print(&report, format);
+        path_found(&report.outcome)
+    }
+
+    fn all_paths(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let limit = input.parsed("limit")?;
+        let samples: Option<usize> = input.parsed("sample")?;
+        let seed = input.parsed("seed")?;
+        if limit.is_some() && samples.is_some() {
+            return Err(Error::Usage(
+                "'--limit' and '--sample' can't be combined".to_owned(),
+            ));
+        }
+        let start = self.page(input, "Start page:")?;
+        let end = self.page(input, "Target page:")?;
+        input.finish()?;
+        self.ensure_graph();
+        self.ensure_reverse_graph();
+
+        let all = self
+            .graph()
+            .all_shortest_paths(self.reverse_graph(), start, end);
+        let mut report = output::ShortestPaths {
+            start: Page::new(start, &self.id_to_title),
+            end: Page::new(end, &self.id_to_title),
+            outcome: output::Outcome::new(&all, &self.id_to_title),
+            steps: None,
+            count: 0,
+            sampled: samples.is_some(),
+            paths: Vec::new(),
+        };
+        if let Ok(all) = &all {
+            let paths = match samples {
+                Some(samples) => {
+                    let mut rng = rng(seed);
+                    (0..samples).map(|_| all.sample(&mut rng)).collect()
                 }
-                "path" => {
-                    self.ensure_graph();
-                    let start = self.read_page("Start page:");
-                    let end = self.read_page("Target page:");
-                    println!();
-
-                    let path = self.graph().find_shortest_path(start, end);
-
-                    if path.is_empty() {
-                        println!("No path found.");
-                    } else {
-                        println!("Reachable in {} steps:", path.len() - 1);
-                        for n in path {
-                            println!("{}", self.id_to_title[&n]);
-                        }
+                None => all.list(limit.unwrap_or(DEFAULT_PATH_LIMIT)),
+            };
+            report.steps = Some(all.length);
+            report.count = all.count();
+            report.paths = paths
+                .iter()
+                .map(|path| Page::list(path, &self.id_to_title))
+                .collect();
+        }
+        output::print(&report, format);
+        path_found(&report.outcome)
+    }
+
+    fn k_shortest_paths(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let start = self.page(input, "Start page:")?;
+        let end = self.page(input, "Target page:")?;
+        let k = match input.next()? {
+            Some(k) => k
+                .parse()
+                .map_err(|_| Error::Usage(format!("Invalid number of paths '{}'", k)))?,
+            None => DEFAULT_K_PATHS,
+        };
+        if k == 0 {
+            return Err(Error::Usage(
+                "The number of paths must be positive".to_owned(),
+            ));
+        }
+        input.finish()?;
+        self.ensure_graph();
+        self.ensure_reverse_graph();
+
+        let paths = self
+            .graph()
+            .k_shortest_paths(self.reverse_graph(), start, end, k);
+        let report = output::KShortestPaths {
+            start: Page::new(start, &self.id_to_title),
+            end: Page::new(end, &self.id_to_title),
+            outcome: output::Outcome::new(&paths, &self.id_to_title),
+            paths: paths
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .map(|path| output::RankedPath {
+                    steps: path.len() - 1,
+                    path: Page::list(path, &self.id_to_title),
+                })
+                .collect(),
+        };
+        output::print(&report, format);
+        path_found(&report.outcome)
+    }
+
+    fn furthest(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let start = self.page(input, "Start page:")?;
+        input.finish()?;
+        self.ensure_graph();
+
+        let (end, dist) = self.graph().find_furthest(start);
+        let furthest = output::Furthest {
+            start: Page::new(start, &self.id_to_title),
+            furthest: Page::new(end, &self.id_to_title),
+            steps: dist,
+        };
+        output::print(&furthest, format);
+        Ok(())
+    }
+
+    fn max(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let start = self.page(input, "Target page:")?;
+        input.finish()?;
+        self.ensure_reverse_graph();
+
+        let (end, dist) = self.reverse_graph().find_furthest(start);
+        let max = output::Max {
+            target: Page::new(start, &self.id_to_title),
+            furthest: Page::new(end, &self.id_to_title),
+            steps: dist,
+        };
+        output::print(&max, format);
+        Ok(())
+    }
+
+    fn distances(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let reverse = input.flag("reverse");
+        let export = input.value("export")?;
+        let page = self.page(input, "Page:")?;
+        input.finish()?;
+        self.check_in_graph(page)?;
+        let graph = if reverse {
+            self.ensure_reverse_graph();
+            self.reverse_graph()
+        } else {
+            self.ensure_graph();
+            self.graph()
+        };
+
+        let dist = graph.distances(page);
+        let mut histogram = Vec::new();
+        for &d in dist.values() {
+            if d > histogram.len() {
+                histogram.resize(d, 0);
+            }
+            if d > 0 {
+                histogram[d - 1] += 1;
+            }
+        }
+        let reachable = dist.len() - 1;
+        let total: usize = dist.values().sum();
+
+        if let Some(export) = export {
+            let mut pages: Vec<_> = dist.iter().map(|(&n, &d)| (d, n)).collect();
+            pages.sort_unstable();
+            let file = File::create(&export).map_err(|e| {
+                eprintln!("Failed to create '{}': {}", export, e);
+                Error::Failed
+            })?;
+            let mut writer = BufWriter::new(file);
+            for (d, n) in pages {
+                writeln!(writer, "{}\t{}\t{}", n, self.id_to_title[&n], d).unwrap();
+            }
+        }
+
+        let report = output::Distances {
+            page: Page::new(page, &self.id_to_title),
+            reverse,
+            histogram: histogram
+                .into_iter()
+                .enumerate()
+                .map(|(i, pages)| output::DistanceCount {
+                    steps: i + 1,
+                    pages,
+                })
+                .collect(),
+            reachable,
+            unreachable: self.graph_pages.len().saturating_sub(dist.len()),
+            mean: if reachable > 0 {
+                Some(total as f64 / reachable as f64)
+            } else {
+                None
+            },
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    fn diameter(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let exact = input.flag("exact");
+        let seed = input.parsed("seed")?;
+        input.finish()?;
+        if exact {
+            return self.exact_diameter(format);
+        }
+        self.ensure_graph();
+
+        let (start, end, dist) = match self.graph().estimate_diameter(&mut rng(seed)) {
+            Some(estimate) => estimate,
+            None => {
+                eprintln!("The link graph is empty.");
+                return Err(Error::Failed);
+            }
+        };
+        let diameter = output::Diameter {
+            estimate: dist,
+            start: Page::new(start, &self.id_to_title),
+            end: Page::new(end, &self.id_to_title),
+        };
+        output::print(&diameter, format);
+        Ok(())
+    }
+
+    /// Computes the diameter and radius of the largest strongly connected component.
+    fn exact_diameter(&mut self, format: Format) -> CmdResult {
+        self.ensure_components();
+        if self.components().count() == 0 {
+            eprintln!("The link graph is empty.");
+            return Err(Error::Failed);
+        }
+        self.ensure_graph();
+        self.ensure_reverse_graph();
+
+        let core = self.components().members(0..1).pop().unwrap();
+        let (graph, reverse) = {
+            let _progress = progress::msg("Building graph of the largest component");
+            (
+                CompactGraph::new(self.graph(), &core),
+                CompactGraph::new(self.reverse_graph(), &core),
+            )
+        };
+        let extremes = diameter::diameter_and_radius(&graph, &reverse);
+        let page = |node: u32| Page::new(core[node as usize], &self.id_to_title);
+        let report = output::ExactDiameter {
+            pages: core.len(),
+            diameter: extremes.diameter,
+            start: page(extremes.start),
+            end: page(extremes.end),
+            radius: extremes.radius,
+            center: page(extremes.center),
+            searches: extremes.searches,
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    fn components_summary(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let limit = input.parsed("limit")?.unwrap_or(DEFAULT_COMPONENT_LIMIT);
+        input.finish()?;
+        self.ensure_components();
+
+        let components = self.components();
+        let listed = components.count().min(limit + 1) as u32;
+        let single_pages = (0..components.count() as u32)
+            .filter(|&c| components.size(c) == 1)
+            .count();
+        let small = components
+            .members(1..listed)
+            .into_iter()
+            .zip(1..)
+            .map(|(pages, id)| output::Component {
+                id,
+                size: pages.len(),
+                sample: Page::list(&pages[..pages.len().min(SAMPLE_SIZE)], &self.id_to_title),
+            })
+            .collect();
+        let report = output::ComponentsSummary {
+            pages: components.pages(),
+            components: components.count(),
+            largest: if components.count() > 0 {
+                components.size(0)
+            } else {
+                0
+            },
+            single_pages,
+            small,
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    fn component(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let page = self.page(input, "Page:")?;
+        input.finish()?;
+        self.ensure_components();
+
+        let component = self.components().of(page);
+        let report = output::ComponentOf {
+            page: Page::new(page, &self.id_to_title),
+            component,
+            size: component.map_or(0, |c| self.components().size(c)),
+            largest: component == Some(0),
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    fn same_component(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let start = self.page(input, "First page:")?;
+        let end = self.page(input, "Second page:")?;
+        input.finish()?;
+        self.ensure_components();
+
+        let start_component = self.components().of(start);
+        let end_component = self.components().of(end);
+        let report = output::SameComponent {
+            start: Page::new(start, &self.id_to_title),
+            end: Page::new(end, &self.id_to_title),
+            same: start_component.is_some() && start_component == end_component,
+            start_component,
+            end_component,
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    fn bow_tie(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let samples = input.parsed("sample")?.unwrap_or(SAMPLE_SIZE);
+        let seed = input.parsed("seed")?;
+        input.finish()?;
+        self.ensure_components();
+        self.ensure_compact_graph();
+        self.ensure_compact_reverse_graph();
+
+        let graph = self.compact_graph();
+        let core: Vec<_> = graph
+            .pages
+            .iter()
+            .map(|&page| self.components().of(page) == Some(0))
+            .collect();
+        let regions = bowtie::regions(graph, self.compact_reverse_graph(), &core);
+
+        let mut rng = rng(seed);
+        let report = output::BowTie {
+            pages: graph.len(),
+            regions: Region::ALL
+                .iter()
+                .map(|&region| {
+                    let pages: Vec<_> = graph
+                        .pages
+                        .iter()
+                        .zip(&regions)
+                        .filter(|&(_, &r)| r == region)
+                        .map(|(&page, _)| page)
+                        .collect();
+                    let mut sample: Vec<_> =
+                        pages.choose_multiple(&mut rng, samples).copied().collect();
+                    sample.sort_unstable_by_key(|page| &self.id_to_title[page]);
+                    output::RegionSize {
+                        region,
+                        size: pages.len(),
+                        sample: Page::list(&sample, &self.id_to_title),
                     }
+                })
+                .collect(),
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    fn rank(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let top = input.parsed("top")?.unwrap_or(DEFAULT_TOP);
+        let params = rank_params(input)?;
+        let page = match input.next()? {
+            Some(title) => Some(self.lookup(&title)?),
+            None => None,
+        };
+        input.finish()?;
+        self.ensure_pagerank(params);
+        self.ensure_compact_graph();
+
+        let scores = &self.pagerank.as_ref().unwrap().scores;
+        self.print_scores("PageRank", scores, page, top, format)
+    }
+
+    fn related(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let top = input.parsed("top")?.unwrap_or(DEFAULT_TOP);
+        let params = rank_params(input)?;
+        let mut seeds = vec![self.page(input, "Seed page:")?];
+        while let Some(title) = input.next()? {
+            seeds.push(self.lookup(&title)?);
+        }
+        input.finish()?;
+        self.ensure_compact_graph();
+        self.ensure_compact_reverse_graph();
+
+        let graph = self.compact_graph();
+        let mut nodes = Vec::new();
+        for &seed in &seeds {
+            nodes.push(self.node(seed)?);
+        }
+        let scores = rank::personalized(graph, self.compact_reverse_graph(), &nodes, params);
+        let ranking = rank::ranking(graph, &scores);
+        let report = output::Related {
+            seeds: Page::list(&seeds, &self.id_to_title),
+            pages: ranking
+                .iter()
+                // Pages that can't be reached from any seed aren't related at all.
+                .filter(|&&node| scores[node as usize] > 0.0 && !nodes.contains(&node))
+                .take(top)
+                .enumerate()
+                .map(|(i, &node)| output::ScoredPage {
+                    position: i + 1,
+                    page: Page::new(graph.pages[node as usize], &self.id_to_title),
+                    score: scores[node as usize],
+                })
+                .collect(),
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    fn hits_command(&mut self, input: &mut Input, authorities: bool) -> CmdResult {
+        let format = self.format(input)?;
+        let top = input.parsed("top")?.unwrap_or(DEFAULT_TOP);
+        let page = match input.next()? {
+            Some(title) => Some(self.lookup(&title)?),
+            None => None,
+        };
+        input.finish()?;
+        self.ensure_hits();
+        self.ensure_compact_graph();
+
+        let hits = self.hits.as_ref().unwrap();
+        if authorities {
+            self.print_scores("authority score", &hits.authorities, page, top, format)
+        } else {
+            self.print_scores("hub score", &hits.hubs, page, top, format)
+        }
+    }
+
+    fn betweenness(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let top = input.parsed("top")?.unwrap_or(DEFAULT_TOP);
+        let samples = input
+            .parsed("samples")?
+            .unwrap_or(DEFAULT_CENTRALITY_SAMPLES);
+        let seed = input.parsed("seed")?;
+        let page = match input.next()? {
+            Some(title) => Some(self.lookup(&title)?),
+            None => None,
+        };
+        input.finish()?;
+        self.ensure_compact_graph();
+
+        let sources = self.sample_nodes(samples, &mut rng(seed));
+        let scores = centrality::betweenness(self.compact_graph(), &sources);
+        self.print_scores("betweenness", &scores, page, top, format)
+    }
+
+    fn closeness(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let top = input.parsed("top")?.unwrap_or(DEFAULT_TOP);
+        let harmonic = input.flag("harmonic");
+        let samples = input
+            .parsed("samples")?
+            .unwrap_or(DEFAULT_CENTRALITY_SAMPLES);
+        let seed = input.parsed("seed")?;
+        let page = match input.next()? {
+            Some(title) => Some(self.lookup(&title)?),
+            None => None,
+        };
+        input.finish()?;
+        self.ensure_compact_graph();
+
+        let graph = self.compact_graph();
+        if let Some(page) = page {
+            let node = self.node(page)?;
+            let closeness = centrality::closeness(graph, node);
+            let report = output::Closeness {
+                page: Page::new(page, &self.id_to_title),
+                closeness: closeness.closeness,
+                harmonic: closeness.harmonic,
+                reachable: closeness.reachable,
+                pages: graph.len(),
+            };
+            output::print(&report, format);
+            return Ok(());
+        }
+
+        self.ensure_compact_reverse_graph();
+        let targets = self.sample_nodes(samples, &mut rng(seed));
+        let estimates = centrality::estimate_closeness(self.compact_reverse_graph(), &targets);
+        let scores: Vec<_> = estimates
+            .iter()
+            .map(|c| if harmonic { c.harmonic } else { c.closeness })
+            .collect();
+        let measure = if harmonic {
+            "harmonic centrality"
+        } else {
+            "closeness"
+        };
+        self.print_scores(measure, &scores, None, top, format)
+    }
+
+    fn separation(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let samples = input
+            .parsed("samples")?
+            .unwrap_or(DEFAULT_CENTRALITY_SAMPLES);
+        let seed = input.parsed("seed")?;
+        input.finish()?;
+        self.ensure_compact_graph();
+
+        let mut rng = rng(seed);
+        let sources = self.sample_nodes(samples, &mut rng);
+        let separation = match separation::separation(self.compact_graph(), &sources, &mut rng) {
+            Some(separation) => separation,
+            None => {
+                eprintln!("None of the sampled pages links to any other page.");
+                return Err(Error::Failed);
+            }
+        };
+        let report = output::Separation {
+            pages: self.compact_graph().len(),
+            sources: sources.len(),
+            reachable: separation.reachable,
+            mean: separation.mean,
+            median: separation.median,
+            effective_diameter: separation.effective_diameter,
+        };
+        output::print(&report, format);
+        Ok(())
+    }
+
+    /// Picks random nodes of the compact graph, or all of them if there are fewer.
+    fn sample_nodes(&self, samples: usize, rng: &mut StdRng) -> Vec<u32> {
+        let n = self.compact_graph().len();
+        if samples >= n {
+            return (0..n as u32).collect();
+        }
+        rand::seq::index::sample(rng, n, samples)
+            .iter()
+            .map(|node| node as u32)
+            .collect()
+    }
+
+    /// Prints the score and position of a page or the top pages by the given scores.
+    fn print_scores(
+        &self,
+        measure: &'static str,
+        scores: &[f64],
+        page: Option<usize>,
+        top: usize,
+        format: Format,
+    ) -> CmdResult {
+        let graph = self.compact_graph();
+        match page {
+            Some(page) => {
+                let node = self.node(page)?;
+                let report = output::Score {
+                    measure,
+                    scored: output::ScoredPage {
+                        position: rank::position(graph, scores, node),
+                        page: Page::new(page, &self.id_to_title),
+                        score: scores[node as usize],
+                    },
+                    pages: graph.len(),
+                };
+                output::print(&report, format);
+            }
+            None => {
+                let ranking = rank::ranking(graph, scores);
+                let report = output::Ranking {
+                    measure,
+                    pages: ranking
+                        .iter()
+                        .take(top)
+                        .enumerate()
+                        .map(|(i, &node)| output::ScoredPage {
+                            position: i + 1,
+                            page: Page::new(graph.pages[node as usize], &self.id_to_title),
+                            score: scores[node as usize],
+                        })
+                        .collect(),
+                };
+                output::print(&report, format);
+            }
+        }
+        Ok(())
+    }
+
+    fn categories_command(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let page = self.page(input, "Page:")?;
+        input.finish()?;
+        self.ensure_categories()?;
+
+        let mut categories = Page::list(&self.categories().categories_of(page), &self.id_to_title);
+        categories.sort_by_key(|category| category.title);
+        let categories = output::PageCategories {
+            page: Page::new(page, &self.id_to_title),
+            categories,
+        };
+        output::print(&categories, format);
+        Ok(())
+    }
+
+    fn top_categories(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let page = self.page(input, "Page:")?;
+        input.finish()?;
+        self.ensure_categories()?;
+
+        let mut top_level = self.categories().find_top_level(page);
+        top_level.sort();
+        let top_level = output::TopCategories {
+            page: Page::new(page, &self.id_to_title),
+            categories: top_level
+                .into_iter()
+                .map(|(category, dist)| output::TopCategory {
+                    category: Page::new(category, &self.id_to_title),
+                    steps: dist,
+                })
+                .collect(),
+        };
+        output::print(&top_level, format);
+        Ok(())
+    }
+
+    fn category_path(&mut self, input: &mut Input) -> CmdResult {
+        let format = self.format(input)?;
+        let start = self.page(input, "Start page:")?;
+        let end = self.page(input, "Target page:")?;
+        input.finish()?;
+        self.ensure_categories()?;
+
+        let path = self.categories().find_shortest_path(start, end);
+        let path = output::Path {
+            verb: "Connected",
+            ..output::Path::new(start, end, &path, &self.id_to_title)
+        };
+        output::print(&path, format);
+        path_found(&path.outcome)
+    }
+
+    fn compare(&mut self, input: &mut Input) -> CmdResult {
+        if !file_exists(self.workspace.path(LANGLINKS_TABLE)) {
+            eprintln!("No language links available.");
+            eprintln!("Please download the 'langlinks' table, delete the generated");
+            eprintln!(
+                "files in '{}' and try again.",
+                self.workspace.path("").display()
+            );
+            return Err(Error::Failed);
+        }
+        let format = self.format(input)?;
+        self.ensure_graph();
+        self.ensure_reverse_graph();
+        let wiki = self.text(input, "Other wiki (e.g. 'dewiki'):")?;
+        self.ensure_other_wiki(&wiki)?;
+        let start = self.page(input, "Start page:")?;
+        let end = self.page(input, "Target page:")?;
+        input.finish()?;
+
+        self.compare_paths(start, end, format)
+    }
+
+    /// Runs a single command given on the command line and returns the exit code.
+    fn run_command(&mut self, command: &str, mut input: Input) -> i32 {
+        if self.load().is_err() {
+            return 1;
+        }
+        match self.execute(command, &mut input) {
+            Ok(()) => 0,
+            Err(Error::Failed) => 1,
+            Err(Error::NoPath) => 3,
+            Err(Error::Usage(e)) => {
+                eprintln!("{}", e);
+                eprintln!("Try 'wiki-analyzer --help' for help.");
+                2
+            }
+        }
+    }
+
+    fn run(&mut self) -> i32 {
+        if self.load().is_err() {
+            return 1;
+        }
+
+        loop {
+            print!("What do you want to do? ");
+            let line = self.read_line();
+            if line.is_empty() {
+                return 0;
+            }
+            let (command, mut input) = match Input::parse_line(&line) {
+                Ok(parsed) => parsed,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
                 }
-                "furthest" => {
-                    self.ensure_graph();
-                    let start = self.read_page("Start page:");
-                    println!();
-
-                    let (end, dist) = self.graph().find_furthest(start);
-                    let title = &self.id_to_title[&end];
-                    println!("The furthest page is '{}' at {} steps.", title, dist);
-                }
-                "max" => {
-                    self.ensure_reverse_graph();
-                    let start = self.read_page("Target page:");
-                    println!();
-
-                    let (end, dist) = self.reverse_graph().find_furthest(start);
-                    let title = &self.id_to_title[&end];
-                    println!(
-                        "The maximal number of steps needed is {} from page '{}'.",
-                        dist, title
-                    );
-                }
-                "diameter" => {
-                    self.ensure_graph();
-                    let (start, end, dist) = self.graph().estimate_diameter();
-                    let title_start = &self.id_to_title[&start];
-                    let title_end = &self.id_to_title[&end];
-                    println!("The estimated diameter is {}.", dist);
-                    println!("when going from '{}'", title_start);
-                    println!("to '{}'.", title_end);
-                }
-                "exit" | "quit" => return,
-                _ => println!("Invalid command. Try 'help' for help."),
+            };
+            match command.as_str() {
+                "" => continue,
+                "exit" | "quit" => return 0,
+                _ => match self.execute(&command, &mut input) {
+                    Ok(()) | Err(Error::Failed) | Err(Error::NoPath) => (),
+                    Err(Error::Usage(e)) => eprintln!("{}. Try 'help' for help.", e),
+                },
             }
             println!();
         }
     }
 }
 
-fn file_exists(path: &str) -> bool {
-    Path::new(path).exists()
+/// Fails if no path was found, so scripts can tell from the exit code.
+fn path_found(outcome: &output::Outcome) -> CmdResult {
+    match outcome {
+        output::Outcome::Found | output::Outcome::SamePage => Ok(()),
+        output::Outcome::NoPath | output::Outcome::UnknownPage { .. } => Err(Error::NoPath),
+    }
+}
+
+/// Takes out the options of the PageRank computation.
+fn rank_params(input: &mut Input) -> Result<rank::Params, Error> {
+    let default = rank::Params::default();
+    let params = rank::Params {
+        damping: input.parsed("damping")?.unwrap_or(default.damping),
+        tolerance: input.parsed("tolerance")?.unwrap_or(default.tolerance),
+        dangling: input.parsed("dangling")?.unwrap_or(default.dangling),
+    };
+    if !(0.0..1.0).contains(&params.damping) {
+        return Err(Error::Usage(
+            "The damping must be at least 0 and less than 1".to_owned(),
+        ));
+    }
+    if params.tolerance <= 0.0 {
+        return Err(Error::Usage("The tolerance must be positive".to_owned()));
+    }
+    Ok(params)
+}
+
+/// A random number generator, seeded for reproducible results if a seed is given.
+fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    }
+}
+
+fn missing_argument(prompt: &str) -> Error {
+    let name = prompt.trim_end_matches(':').to_lowercase();
+    Error::Usage(format!("Missing argument: {}", name))
+}
+
+fn print_commands() {
+    println!(
+        "\
+Commands:
+    links <page>                    List all the links on a page
+    path <start> <target>           Find the shortest path from one page to another
+                                    --avoid <page>: Don't pass this page, can be repeated
+                                    --avoid-regex <regex>: Don't pass pages whose title matches
+                                    --via <page>: Pass these pages in the given order
+    wpath <start> <target>          Find the cheapest path, where linking to pages with many links costs more
+                                    --degree in|out: Count the links to or from the page (default: in)
+                                    --cost log|sqrt|linear: How the cost grows with the links (default: log)
+    paths <start> <target>          Count all shortest paths and list them (--limit <n>)
+                                    or pick random ones (--sample <n> [--seed <s>])
+    kpaths <start> <target> [<k>]   Find the k shortest paths that don't visit a page twice (default: 5)
+    furthest <start>                Find the page furthest away from a starting point
+    max <target>                    Find the maximal number of steps needed to get to a page from anywhere
+    distances <page>                Count the pages at each distance from a page
+                                    --reverse: Count the distances to the page instead
+                                    --export <file>: Write the distance of every reachable page to a file
+    separation                      Estimate how many steps apart pages are: the mean and median distance,
+                                    the effective diameter (90% of pages are at most that far) and the reachable pairs
+                                    --samples <n>: How many random start pages to use (default: 100, --seed <s>)
+    diameter                        Approximate the diameter of the link graph (i.e. how far the furthest two pages are apart)
+                                    --seed <s>: Seed for picking the random start page
+                                    --exact: Compute the exact diameter and radius of the largest strongly connected component
+    components                      Count the strongly connected components, i.e. groups of pages that can all reach each other
+                                    --limit <n>: How many of the components after the largest one to list (default: 10)
+    component <page>                Show the strongly connected component of a page
+    samecomponent <page> <page>     Check whether two pages are in the same strongly connected component
+    bowtie                          Split the link graph into the core, IN, OUT, tendrils, tubes and disconnected pages
+                                    --sample <n>: How many random pages of each region to show (default: 5)
+    rank [<page>]                   Show the PageRank of a page or the top pages (--top <n>, default: 10)
+                                    --damping <d>: Probability of following a link (default: 0.85)
+                                    --tolerance <t>: Stop once the scores change less than this (default: 1e-6)
+                                    --dangling teleport|drop|self: Where the score of pages without links goes
+    hubs [<page>]                   Show the hub score of a page or the top hubs, i.e. pages linking to many authorities (--top <n>)
+    authorities [<page>]            Show the authority score of a page or the top authorities, i.e. pages linked from many hubs (--top <n>)
+    betweenness [<page>]            Estimate on how many shortest paths between other pages a page lies (--top <n>)
+                                    --samples <n>: How many random start pages to use (default: 100, --seed <s>)
+    closeness [<page>]              Show how close a page is to the pages it can reach, or estimate the closest pages (--top <n>)
+                                    --harmonic: Rank by harmonic centrality instead, which works better for pages reaching few others
+                                    --samples <n>: How many random target pages to use (default: 100, --seed <s>)
+    related <page>...               Find the pages most related to the given ones by personalized PageRank
+                                    (--top <n>, default: 10; takes the options of rank as well)
+    categories <page>               List the categories of a page
+    topcategories <page>            Find the top-level categories above a page
+    catpath <start> <target>        Find the shortest path between two pages in the category tree
+    compare <wiki> <start> <target> Compare the shortest path between two pages with the one in another language
+    help
+    exit"
+    );
+}
+
+/// The pages by title and by ID, and the IDs of the pages in the given namespaces, sorted.
+type Pages = (HashMap<String, usize>, HashMap<usize, String>, Vec<usize>);
+
+fn load_pages(path: impl AsRef<Path>, namespaces: &HashSet<i64>) -> Pages {
+    let _progress = progress::msg("Loading pages");
+    let mut title_to_id = HashMap::new();
+    let mut id_to_title = HashMap::new();
+    let mut in_namespaces = Vec::new();
+
+    let reader = BufReader::new(File::open(path).unwrap());
+    for line in reader.lines() {
+        let line = line.unwrap();
+        let mut iter = line.split('\t');
+        let id: usize = iter.next().unwrap().parse().unwrap();
+        let namespace: i64 = iter.next().unwrap().parse().unwrap();
+        let title = display_title(namespace, iter.next().unwrap());
+        id_to_title.insert(id, title.clone());
+        title_to_id.insert(title, id);
+        if namespaces.contains(&namespace) {
+            in_namespaces.push(id);
+        }
+    }
+
+    in_namespaces.sort_unstable();
+    (title_to_id, id_to_title, in_namespaces)
+}
+
+/// Removes all files extracted from the dumps or generated from them,
+/// as they depend on the namespaces.
+fn remove_generated_files(ws: &Workspace) {
+    for file in &[
+        PAGE_TABLE,
+        REDIRECTS_TABLE,
+        LINKS_TABLE,
+        LINKTARGET_TABLE,
+        CATEGORYLINKS_TABLE,
+        LANGLINKS_TABLE,
+        PAGES,
+        GRAPH,
+        GRAPH_REVERSE,
+        CATEGORIES,
+        CATEGORIES_REVERSE,
+        COMPONENTS,
+        PAGERANK,
+        HITS,
+        NAMESPACES,
+    ] {
+        let path = ws.path(file);
+        if file_exists(&path) {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
+
+fn file_exists(path: impl AsRef<Path>) -> bool {
+    path.as_ref().exists()
+}
+
+/// Canonical namespace names, which are understood by every wiki regardless of its language.
+fn namespace_name(namespace: i64) -> Option<&'static str> {
+    Some(match namespace {
+        0 => "",
+        1 => "Talk",
+        2 => "User",
+        3 => "User talk",
+        4 => "Project",
+        5 => "Project talk",
+        6 => "File",
+        7 => "File talk",
+        8 => "MediaWiki",
+        9 => "MediaWiki talk",
+        10 => "Template",
+        11 => "Template talk",
+        12 => "Help",
+        13 => "Help talk",
+        14 => "Category",
+        15 => "Category talk",
+        _ => return None,
+    })
+}
+
+fn display_title(namespace: i64, title: &str) -> String {
+    match namespace_name(namespace) {
+        Some("") => title.to_owned(),
+        Some(name) => format!("{}:{}", name, title),
+        None => format!("{}:{}", namespace, title),
+    }
 }
 
 fn main() {
-    Main::new().run();
+    let mut options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Try 'wiki-analyzer --help' for help.");
+            std::process::exit(2);
+        }
+    };
+    let workspace = match Workspace::select(&options.data_dir, options.wiki.as_deref()) {
+        Ok(workspace) => workspace,
+        Err(()) => std::process::exit(1),
+    };
+    let command = options.command.take();
+    let mut main = Main::new(options, workspace);
+    let code = match command {
+        Some((command, input)) => main.run_command(&command, input),
+        None => main.run(),
+    };
+    std::process::exit(code);
 }
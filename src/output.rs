@@ -0,0 +1,946 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::bowtie::Region;
+use crate::graph::PathError;
+use crate::separation::Estimate;
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Text,
+    /// A single JSON document per result.
+    Json,
+    /// One JSON value per line, e.g. one per page of a path.
+    JsonLines,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "jsonl" => Ok(Format::JsonLines),
+            _ => Err(format!(
+                "Invalid format '{}', expected 'text', 'json' or 'jsonl'",
+                s
+            )),
+        }
+    }
+}
+
+/// The result of a command, which can be printed in any format.
+pub trait Report: Serialize {
+    fn print_text(&self);
+
+    /// The values printed one per line in JSON Lines mode.
+    fn lines(&self) -> Vec<String> {
+        vec![serde_json::to_string(self).unwrap()]
+    }
+}
+
+pub fn print(report: &impl Report, format: Format) {
+    match format {
+        Format::Text => report.print_text(),
+        Format::Json => println!("{}", serde_json::to_string_pretty(report).unwrap()),
+        Format::JsonLines => {
+            for line in report.lines() {
+                println!("{}", line);
+            }
+        }
+    }
+}
+
+fn to_lines<T: Serialize>(items: &[T]) -> Vec<String> {
+    items
+        .iter()
+        .map(|item| serde_json::to_string(item).unwrap())
+        .collect()
+}
+
+#[derive(Serialize, Clone, Copy)]
+pub struct Page<'a> {
+    pub id: usize,
+    pub title: &'a str,
+}
+
+impl<'a> Page<'a> {
+    pub fn new(id: usize, id_to_title: &'a HashMap<usize, String>) -> Page<'a> {
+        Page {
+            id,
+            title: &id_to_title[&id],
+        }
+    }
+
+    pub fn list(ids: &[usize], id_to_title: &'a HashMap<usize, String>) -> Vec<Page<'a>> {
+        ids.iter().map(|&id| Page::new(id, id_to_title)).collect()
+    }
+}
+
+/// How a path search ended.
+#[derive(Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Outcome<'a> {
+    Found,
+    /// The start and the target are the same page.
+    SamePage,
+    NoPath,
+    /// The page neither has links nor is linked to, so it isn't part of the graph.
+    UnknownPage {
+        page: Page<'a>,
+    },
+}
+
+/// The result of a path search, which may be a path from the start to itself without any steps.
+pub trait Steps {
+    fn is_empty_path(&self) -> bool;
+}
+
+impl Steps for Vec<usize> {
+    fn is_empty_path(&self) -> bool {
+        self.len() == 1
+    }
+}
+
+impl Steps for (Vec<usize>, f64) {
+    fn is_empty_path(&self) -> bool {
+        self.0.is_empty_path()
+    }
+}
+
+impl Steps for Vec<Vec<usize>> {
+    fn is_empty_path(&self) -> bool {
+        self.first().is_some_and(Steps::is_empty_path)
+    }
+}
+
+impl Steps for crate::graph::ShortestPaths {
+    fn is_empty_path(&self) -> bool {
+        self.length == 0
+    }
+}
+
+impl<'a> Outcome<'a> {
+    /// Only a path without steps counts as `SamePage`, not a round trip from a page to itself.
+    pub fn new<T: Steps>(
+        result: &Result<T, PathError>,
+        id_to_title: &'a HashMap<usize, String>,
+    ) -> Outcome<'a> {
+        match result {
+            Ok(found) if found.is_empty_path() => Outcome::SamePage,
+            Ok(_) => Outcome::Found,
+            Err(PathError::NoPath) => Outcome::NoPath,
+            Err(PathError::UnknownPage(page)) => Outcome::UnknownPage {
+                page: Page::new(*page, id_to_title),
+            },
+        }
+    }
+
+    /// The lines to print in JSON Lines mode: the pages if a path was found,
+    /// otherwise only the outcome.
+    fn lines<T: Serialize>(&self, items: &[T]) -> Vec<String> {
+        match self {
+            Outcome::Found | Outcome::SamePage => to_lines(items),
+            _ => vec![serde_json::to_string(self).unwrap()],
+        }
+    }
+
+    /// Like `print_unless_found`, but names the wiki that was searched.
+    /// Paths from a page to itself count as found.
+    fn print_unless_found_in(&self, wiki: &str) -> bool {
+        match self {
+            Outcome::Found | Outcome::SamePage => return false,
+            Outcome::NoPath => println!("No path found in {}.", wiki),
+            Outcome::UnknownPage { page } => println!(
+                "No path found in {}, '{}' neither has links nor is linked to.",
+                wiki, page.title
+            ),
+        }
+        true
+    }
+
+    /// Explains why no path is printed and returns `true`, or returns `false` if one was found.
+    fn print_unless_found(&self) -> bool {
+        match self {
+            Outcome::Found => return false,
+            Outcome::SamePage => println!("Start and target are the same page."),
+            Outcome::NoPath => println!("No path found."),
+            Outcome::UnknownPage { page } => println!(
+                "No path found, '{}' neither has links nor is linked to.",
+                page.title
+            ),
+        }
+        true
+    }
+}
+
+#[derive(Serialize)]
+pub struct Links<'a> {
+    pub page: Page<'a>,
+    pub links: Vec<Page<'a>>,
+}
+
+impl Report for Links<'_> {
+    fn print_text(&self) {
+        println!("{} links:", self.links.len());
+        for link in &self.links {
+            println!("{}", link.title);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.links)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Path<'a> {
+    pub start: Page<'a>,
+    pub end: Page<'a>,
+    #[serde(flatten)]
+    pub outcome: Outcome<'a>,
+    /// The number of links to follow, `None` if there is no path.
+    pub steps: Option<usize>,
+    pub path: Vec<Page<'a>>,
+    /// How the path is described in text, e.g. "Reachable" or "Connected".
+    #[serde(skip)]
+    pub verb: &'static str,
+}
+
+impl<'a> Path<'a> {
+    pub fn new(
+        start: usize,
+        end: usize,
+        result: &Result<Vec<usize>, PathError>,
+        id_to_title: &'a HashMap<usize, String>,
+    ) -> Path<'a> {
+        let path = result.as_deref().unwrap_or_default();
+        Path {
+            start: Page::new(start, id_to_title),
+            end: Page::new(end, id_to_title),
+            outcome: Outcome::new(result, id_to_title),
+            steps: path.len().checked_sub(1),
+            path: Page::list(path, id_to_title),
+            verb: "Reachable",
+        }
+    }
+}
+
+impl Report for Path<'_> {
+    fn print_text(&self) {
+        if self.outcome.print_unless_found() {
+            return;
+        }
+        println!("{} in {} steps:", self.verb, self.path.len() - 1);
+        for page in &self.path {
+            println!("{}", page.title);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.outcome.lines(&self.path)
+    }
+}
+
+#[derive(Serialize)]
+pub struct WeightedPath<'a> {
+    pub start: Page<'a>,
+    pub end: Page<'a>,
+    #[serde(flatten)]
+    pub outcome: Outcome<'a>,
+    /// The number of links to follow, `None` if there is no path.
+    pub steps: Option<usize>,
+    /// The sum of the costs of all links on the path.
+    pub cost: Option<f64>,
+    pub path: Vec<Page<'a>>,
+}
+
+impl Report for WeightedPath<'_> {
+    fn print_text(&self) {
+        if self.outcome.print_unless_found() {
+            return;
+        }
+        println!(
+            "Reachable in {} steps with a cost of {:.2}:",
+            self.path.len() - 1,
+            self.cost.unwrap()
+        );
+        for page in &self.path {
+            println!("{}", page.title);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.outcome.lines(&self.path)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ShortestPaths<'a> {
+    pub start: Page<'a>,
+    pub end: Page<'a>,
+    #[serde(flatten)]
+    pub outcome: Outcome<'a>,
+    /// The number of links on each path, `None` if there is no path.
+    pub steps: Option<usize>,
+    /// The total number of shortest paths, saturating at the maximum of a `u128`.
+    pub count: u128,
+    /// Whether the paths were sampled at random instead of listed in order.
+    pub sampled: bool,
+    pub paths: Vec<Vec<Page<'a>>>,
+}
+
+impl Report for ShortestPaths<'_> {
+    fn print_text(&self) {
+        if self.outcome.print_unless_found() {
+            return;
+        }
+        let steps = self.steps.unwrap();
+        if self.count == u128::MAX {
+            println!(
+                "There are at least {} shortest paths with {} steps.",
+                self.count, steps
+            );
+        } else if self.count == 1 {
+            println!("There is 1 shortest path with {} steps.", steps);
+        } else {
+            println!(
+                "There are {} shortest paths with {} steps.",
+                self.count, steps
+            );
+        }
+        if self.sampled {
+            println!("{} picked at random:", self.paths.len());
+        } else if (self.paths.len() as u128) < self.count {
+            println!("The first {}:", self.paths.len());
+        }
+        for path in &self.paths {
+            let titles: Vec<_> = path.iter().map(|page| page.title).collect();
+            println!("{}", titles.join(" -> "));
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.outcome.lines(&self.paths)
+    }
+}
+
+#[derive(Serialize)]
+pub struct RankedPath<'a> {
+    pub steps: usize,
+    pub path: Vec<Page<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct KShortestPaths<'a> {
+    pub start: Page<'a>,
+    pub end: Page<'a>,
+    #[serde(flatten)]
+    pub outcome: Outcome<'a>,
+    pub paths: Vec<RankedPath<'a>>,
+}
+
+impl Report for KShortestPaths<'_> {
+    fn print_text(&self) {
+        if self.outcome.print_unless_found() {
+            return;
+        }
+        if self.paths.len() == 1 {
+            println!("The shortest path:");
+        } else {
+            println!("The {} shortest paths:", self.paths.len());
+        }
+        for path in &self.paths {
+            let titles: Vec<_> = path.path.iter().map(|page| page.title).collect();
+            println!("{} steps: {}", path.steps, titles.join(" -> "));
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.outcome.lines(&self.paths)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Furthest<'a> {
+    pub start: Page<'a>,
+    pub furthest: Page<'a>,
+    pub steps: usize,
+}
+
+impl Report for Furthest<'_> {
+    fn print_text(&self) {
+        println!(
+            "The furthest page is '{}' at {} steps.",
+            self.furthest.title, self.steps
+        );
+    }
+}
+
+#[derive(Serialize)]
+pub struct Max<'a> {
+    pub target: Page<'a>,
+    pub furthest: Page<'a>,
+    pub steps: usize,
+}
+
+impl Report for Max<'_> {
+    fn print_text(&self) {
+        println!(
+            "The maximal number of steps needed is {} from page '{}'.",
+            self.steps, self.furthest.title
+        );
+    }
+}
+
+#[derive(Serialize)]
+pub struct DistanceCount {
+    pub steps: usize,
+    pub pages: usize,
+}
+
+#[derive(Serialize)]
+pub struct Distances<'a> {
+    pub page: Page<'a>,
+    /// Whether the distances are to the page instead of from it.
+    pub reverse: bool,
+    /// The number of pages at each distance, starting at 1 step.
+    pub histogram: Vec<DistanceCount>,
+    pub reachable: usize,
+    pub unreachable: usize,
+    /// The mean distance of all reachable pages, `None` if there are none.
+    pub mean: Option<f64>,
+}
+
+impl Report for Distances<'_> {
+    fn print_text(&self) {
+        if self.reverse {
+            println!(
+                "{} pages can reach '{}', {} can't.",
+                self.reachable, self.page.title, self.unreachable
+            );
+        } else {
+            println!(
+                "{} pages can be reached from '{}', {} can't.",
+                self.reachable, self.page.title, self.unreachable
+            );
+        }
+        for count in &self.histogram {
+            println!("{:>3} steps: {}", count.steps, count.pages);
+        }
+        if let Some(mean) = self.mean {
+            println!("The mean distance is {:.2} steps.", mean);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.histogram)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Component<'a> {
+    pub id: u32,
+    pub size: usize,
+    /// The first pages of the component by ID.
+    pub sample: Vec<Page<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct ComponentsSummary<'a> {
+    pub pages: usize,
+    pub components: usize,
+    /// The size of the largest component.
+    pub largest: usize,
+    /// The number of pages that are in a component of their own.
+    pub single_pages: usize,
+    /// The next largest components after the largest one.
+    pub small: Vec<Component<'a>>,
+}
+
+impl Report for ComponentsSummary<'_> {
+    fn print_text(&self) {
+        println!(
+            "The link graph has {} strongly connected components.",
+            self.components
+        );
+        println!(
+            "The largest one contains {} of {} pages ({:.1}%).",
+            self.largest,
+            self.pages,
+            percent(self.largest, self.pages)
+        );
+        println!(
+            "{} pages are in a component of their own.",
+            self.single_pages
+        );
+        if self.small.is_empty() {
+            return;
+        }
+        println!("The next largest components:");
+        for component in &self.small {
+            println!(
+                "#{} with {} pages: {}",
+                component.id,
+                component.size,
+                sample_titles(&component.sample, component.size)
+            );
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.small)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ComponentOf<'a> {
+    pub page: Page<'a>,
+    /// The component of the page, `None` if it isn't part of the link graph.
+    pub component: Option<u32>,
+    pub size: usize,
+    pub largest: bool,
+}
+
+impl Report for ComponentOf<'_> {
+    fn print_text(&self) {
+        match self.component {
+            None => println!("'{}' is not part of the link graph.", self.page.title),
+            Some(_) if self.largest => println!(
+                "'{}' is in the largest strongly connected component with {} pages.",
+                self.page.title, self.size
+            ),
+            Some(component) => println!(
+                "'{}' is in strongly connected component #{} with {} pages.",
+                self.page.title, component, self.size
+            ),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct SameComponent<'a> {
+    pub start: Page<'a>,
+    pub end: Page<'a>,
+    pub same: bool,
+    pub start_component: Option<u32>,
+    pub end_component: Option<u32>,
+}
+
+impl Report for SameComponent<'_> {
+    fn print_text(&self) {
+        if self.same {
+            println!(
+                "'{}' and '{}' are in the same strongly connected component,",
+                self.start.title, self.end.title
+            );
+            println!("so they can reach each other.");
+        } else {
+            println!(
+                "'{}' and '{}' are in different strongly connected components,",
+                self.start.title, self.end.title
+            );
+            println!("so at least one of them can't reach the other.");
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct RegionSize<'a> {
+    pub region: Region,
+    pub size: usize,
+    /// Some pages of the region picked at random.
+    pub sample: Vec<Page<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct BowTie<'a> {
+    pub pages: usize,
+    pub regions: Vec<RegionSize<'a>>,
+}
+
+impl Report for BowTie<'_> {
+    fn print_text(&self) {
+        println!(
+            "Bow-tie structure of the link graph with {} pages:",
+            self.pages
+        );
+        for region in &self.regions {
+            println!(
+                "{:<13}{:>10} {:>5.1}%  {}",
+                region.region.name(),
+                region.size,
+                percent(region.size, self.pages),
+                sample_titles(&region.sample, region.size)
+            );
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.regions)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ScoredPage<'a> {
+    pub position: usize,
+    #[serde(flatten)]
+    pub page: Page<'a>,
+    pub score: f64,
+}
+
+#[derive(Serialize)]
+pub struct Ranking<'a> {
+    /// What the pages are ranked by, e.g. "PageRank".
+    pub measure: &'static str,
+    pub pages: Vec<ScoredPage<'a>>,
+}
+
+impl Report for Ranking<'_> {
+    fn print_text(&self) {
+        println!("Top {} pages by {}:", self.pages.len(), self.measure);
+        for scored in &self.pages {
+            println!(
+                "{:>6}. {} ({})",
+                scored.position,
+                scored.page.title,
+                format_score(scored.score)
+            );
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.pages)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Related<'a> {
+    pub seeds: Vec<Page<'a>>,
+    /// The pages with the highest personalized PageRank, without the seeds.
+    pub pages: Vec<ScoredPage<'a>>,
+}
+
+impl Report for Related<'_> {
+    fn print_text(&self) {
+        let seeds: Vec<_> = self.seeds.iter().map(|seed| seed.title).collect();
+        if self.pages.is_empty() {
+            println!("No other pages can be reached from {}.", seeds.join(", "));
+            return;
+        }
+        println!("Pages most related to {}:", seeds.join(", "));
+        for scored in &self.pages {
+            println!(
+                "{:>6}. {} ({})",
+                scored.position,
+                scored.page.title,
+                format_score(scored.score)
+            );
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.pages)
+    }
+}
+
+#[derive(Serialize)]
+pub struct Score<'a> {
+    pub measure: &'static str,
+    #[serde(flatten)]
+    pub scored: ScoredPage<'a>,
+    /// The number of ranked pages.
+    pub pages: usize,
+}
+
+impl Report for Score<'_> {
+    fn print_text(&self) {
+        println!(
+            "{} of '{}': {} (position {} of {})",
+            capitalize(self.measure),
+            self.scored.page.title,
+            format_score(self.scored.score),
+            self.scored.position,
+            self.pages
+        );
+    }
+}
+
+#[derive(Serialize)]
+pub struct Closeness<'a> {
+    #[serde(flatten)]
+    pub page: Page<'a>,
+    pub closeness: f64,
+    pub harmonic: f64,
+    /// The number of other pages reachable from the page.
+    pub reachable: usize,
+    pub pages: usize,
+}
+
+impl Report for Closeness<'_> {
+    fn print_text(&self) {
+        println!(
+            "'{}' reaches {} of the other {} pages ({:.1}%).",
+            self.page.title,
+            self.reachable,
+            self.pages - 1,
+            percent(self.reachable, self.pages - 1)
+        );
+        println!("Closeness: {}", format_score(self.closeness));
+        println!("Harmonic centrality: {}", format_score(self.harmonic));
+    }
+}
+
+#[derive(Serialize)]
+pub struct Diameter<'a> {
+    /// A lower bound for the diameter found by the heuristic.
+    pub estimate: usize,
+    pub start: Page<'a>,
+    pub end: Page<'a>,
+}
+
+impl Report for Diameter<'_> {
+    fn print_text(&self) {
+        println!("The estimated diameter is {}.", self.estimate);
+        println!("when going from '{}'", self.start.title);
+        println!("to '{}'.", self.end.title);
+    }
+}
+
+#[derive(Serialize)]
+pub struct ExactDiameter<'a> {
+    /// The number of pages in the largest strongly connected component.
+    pub pages: usize,
+    pub diameter: usize,
+    pub start: Page<'a>,
+    pub end: Page<'a>,
+    pub radius: usize,
+    /// A page from which all others in the component can be reached within `radius` steps.
+    pub center: Page<'a>,
+    /// The number of pages that had to be searched from.
+    pub searches: usize,
+}
+
+impl Report for ExactDiameter<'_> {
+    fn print_text(&self) {
+        println!(
+            "The largest strongly connected component has {} pages.",
+            self.pages
+        );
+        println!(
+            "Its diameter is {}, when going from '{}' to '{}'.",
+            self.diameter, self.start.title, self.end.title
+        );
+        println!(
+            "Its radius is {}, as all pages can be reached from '{}' in that many steps.",
+            self.radius, self.center.title
+        );
+        println!("Found by searching from {} pages.", self.searches);
+    }
+}
+
+#[derive(Serialize)]
+pub struct Separation {
+    pub pages: usize,
+    /// The number of sampled pages the distances were measured from.
+    pub sources: usize,
+    /// The fraction of pairs of pages where the second one can be reached from the first.
+    pub reachable: Estimate,
+    pub mean: Estimate,
+    pub median: Estimate,
+    /// The distance within which 90% of the reachable pairs are.
+    pub effective_diameter: Estimate,
+}
+
+impl Report for Separation {
+    fn print_text(&self) {
+        println!(
+            "Measured from {} of {} pages, with 95% confidence intervals:",
+            self.sources, self.pages
+        );
+        let line = |name: &str, estimate: &Estimate, scale: f64, unit: &str| {
+            println!(
+                "{:<20} {:.2}{} ({:.2}{} to {:.2}{})",
+                name,
+                estimate.value * scale,
+                unit,
+                estimate.low * scale,
+                unit,
+                estimate.high * scale,
+                unit
+            );
+        };
+        line("Reachable pairs:", &self.reachable, 100.0, "%");
+        line("Mean distance:", &self.mean, 1.0, "");
+        line("Median distance:", &self.median, 1.0, "");
+        line("Effective diameter:", &self.effective_diameter, 1.0, "");
+    }
+}
+
+#[derive(Serialize)]
+pub struct PageCategories<'a> {
+    pub page: Page<'a>,
+    pub categories: Vec<Page<'a>>,
+}
+
+impl Report for PageCategories<'_> {
+    fn print_text(&self) {
+        println!("{} categories:", self.categories.len());
+        for category in &self.categories {
+            println!("{}", category.title);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.categories)
+    }
+}
+
+#[derive(Serialize)]
+pub struct TopCategory<'a> {
+    #[serde(flatten)]
+    pub category: Page<'a>,
+    pub steps: usize,
+}
+
+#[derive(Serialize)]
+pub struct TopCategories<'a> {
+    pub page: Page<'a>,
+    pub categories: Vec<TopCategory<'a>>,
+}
+
+impl Report for TopCategories<'_> {
+    fn print_text(&self) {
+        println!("{} top-level categories:", self.categories.len());
+        for top in &self.categories {
+            println!("{} ({} steps)", top.category.title, top.steps);
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        to_lines(&self.categories)
+    }
+}
+
+/// A page of the other wiki in a comparison.
+#[derive(Serialize)]
+pub struct OtherPage<'a> {
+    #[serde(flatten)]
+    pub page: Page<'a>,
+    /// The corresponding page in this wiki, if there is one.
+    pub counterpart: Option<Page<'a>>,
+}
+
+#[derive(Serialize)]
+pub struct Comparison<'a> {
+    /// The language of the other wiki.
+    pub lang: &'a str,
+    #[serde(flatten)]
+    pub outcome: Outcome<'a>,
+    /// How the search in the other wiki ended.
+    pub other_outcome: Outcome<'a>,
+    pub path: Vec<Page<'a>>,
+    pub other_path: Vec<OtherPage<'a>>,
+    /// How many steps longer the path in the other wiki is, negative if it is shorter.
+    pub difference: Option<i64>,
+    pub identical: bool,
+    /// The last page both paths have in common before they diverge.
+    pub diverge_after: Option<Page<'a>>,
+    /// The number of intermediate pages that are on both paths.
+    pub shared: Option<usize>,
+}
+
+impl Report for Comparison<'_> {
+    fn print_text(&self) {
+        if !self.outcome.print_unless_found_in("this wiki") {
+            println!("Reachable in {} steps in this wiki:", self.path.len() - 1);
+            for page in &self.path {
+                println!("{}", page.title);
+            }
+        }
+        println!();
+
+        let other_wiki = format!("the '{}' wiki", self.lang);
+        if !self.other_outcome.print_unless_found_in(&other_wiki) {
+            println!(
+                "Reachable in {} steps in the '{}' wiki:",
+                self.other_path.len() - 1,
+                self.lang
+            );
+            for other in &self.other_path {
+                match other.counterpart {
+                    Some(counterpart) => println!("{} ({})", other.page.title, counterpart.title),
+                    None => println!("{}", other.page.title),
+                }
+            }
+        }
+
+        let difference = match self.difference {
+            Some(difference) => difference,
+            None => return,
+        };
+        println!();
+
+        if difference == 0 {
+            println!("Both paths have the same length.");
+        } else if difference > 0 {
+            println!(
+                "The path in the '{}' wiki is {} steps longer.",
+                self.lang, difference
+            );
+        } else {
+            println!(
+                "The path in the '{}' wiki is {} steps shorter.",
+                self.lang, -difference
+            );
+        }
+
+        if self.identical {
+            println!("The paths are identical.");
+        } else if let Some(page) = self.diverge_after {
+            println!("The paths diverge after '{}'.", page.title);
+        }
+        if let Some(shared) = self.shared {
+            println!("{} intermediate pages are on both paths.", shared);
+        }
+    }
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        100.0 * part as f64 / total as f64
+    }
+}
+
+/// The titles of some pages of a group with `size` pages in total.
+fn sample_titles(sample: &[Page], size: usize) -> String {
+    let titles: Vec<_> = sample.iter().map(|page| page.title).collect();
+    if size > sample.len() {
+        format!("{}, ... ({} more)", titles.join(", "), size - sample.len())
+    } else {
+        titles.join(", ")
+    }
+}
+
+/// The text with its first letter in upper case, to start a sentence with it.
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Formats a score with four significant digits, using an exponent for very small or large ones.
+fn format_score(score: f64) -> String {
+    if score == 0.0 || (1e-3..1e6).contains(&score.abs()) {
+        format!("{:.4}", score)
+    } else {
+        format!("{:.4e}", score)
+    }
+}
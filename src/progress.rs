@@ -6,13 +6,13 @@ pub struct ProgressMsg {}
 
 impl Drop for ProgressMsg {
     fn drop(&mut self) {
-        println!(" done.");
+        eprintln!(" done.");
     }
 }
 
 pub fn msg(msg: &str) -> ProgressMsg {
-    print!("{}...", msg);
-    std::io::stdout().flush().unwrap();
+    eprint!("{}...", msg);
+    std::io::stderr().flush().unwrap();
     ProgressMsg {}
 }
 
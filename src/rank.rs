@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::str::FromStr;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::CompactGraph;
+use crate::workspace::Workspace;
+
+/// The maximal number of iterations, in case the scores don't converge.
+pub const MAX_ITERATIONS: usize = 1000;
+
+/// What happens to the score of pages without any links.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Dangling {
+    /// It is spread like a random jump, i.e. over all pages or the seeds of a personalized rank.
+    Teleport,
+    /// It is dropped and the scores are normalized at the end.
+    Drop,
+    /// It stays on the page as if it linked to itself.
+    SelfLoop,
+}
+
+impl FromStr for Dangling {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Dangling, ()> {
+        match s {
+            "teleport" => Ok(Dangling::Teleport),
+            "drop" => Ok(Dangling::Drop),
+            "self" => Ok(Dangling::SelfLoop),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Params {
+    /// The probability of following a link instead of jumping to a random page.
+    pub damping: f64,
+    /// The iteration stops once the scores change by less than this in total.
+    pub tolerance: f64,
+    pub dangling: Dangling,
+}
+
+impl Default for Params {
+    fn default() -> Params {
+        Params {
+            damping: 0.85,
+            tolerance: 1e-6,
+            dangling: Dangling::Teleport,
+        }
+    }
+}
+
+/// The PageRank of all pages of a `CompactGraph`, in the order of its nodes.
+#[derive(Serialize, Deserialize)]
+pub struct PageRank {
+    pub params: Params,
+    pub iterations: usize,
+    pub scores: Vec<f64>,
+}
+
+impl PageRank {
+    pub fn compute(graph: &CompactGraph, reverse: &CompactGraph, params: Params) -> PageRank {
+        let _progress = crate::progress::msg("Computing PageRank");
+        let n = graph.len();
+        let teleport = vec![1.0 / n as f64; n];
+        let (scores, iterations) = power_iteration(graph, reverse, &teleport, params);
+        PageRank {
+            params,
+            iterations,
+            scores,
+        }
+    }
+
+    pub fn load(workspace: &Workspace) -> PageRank {
+        let _progress = crate::progress::msg("Loading PageRank");
+        let file = File::open(workspace.path(crate::PAGERANK)).unwrap();
+        bincode::deserialize_from(BufReader::new(file)).unwrap()
+    }
+
+    pub fn save(&self, workspace: &Workspace) {
+        let writer = BufWriter::new(File::create(workspace.path(crate::PAGERANK)).unwrap());
+        bincode::serialize_into(writer, self).unwrap();
+    }
+}
+
+/// Personalized PageRank, where the surfer only ever jumps back to one of the seeds.
+/// Pages with a high score are the ones that are closely linked to the seeds.
+pub fn personalized(
+    graph: &CompactGraph,
+    reverse: &CompactGraph,
+    seeds: &[u32],
+    params: Params,
+) -> Vec<f64> {
+    let _progress = crate::progress::msg("Computing personalized PageRank");
+    let mut teleport = vec![0.0; graph.len()];
+    for &seed in seeds {
+        teleport[seed as usize] += 1.0 / seeds.len() as f64;
+    }
+    power_iteration(graph, reverse, &teleport, params).0
+}
+
+/// Computes the stationary distribution of a random surfer, who follows a random link
+/// with probability `damping` and otherwise jumps to a page picked from `teleport`.
+/// Returns the scores of all nodes and the number of iterations needed.
+pub fn power_iteration(
+    graph: &CompactGraph,
+    reverse: &CompactGraph,
+    teleport: &[f64],
+    params: Params,
+) -> (Vec<f64>, usize) {
+    let n = graph.len();
+    let d = params.damping;
+    let out_degrees: Vec<_> = (0..n as u32).map(|i| graph.links(i).len()).collect();
+    let mut scores = teleport.to_vec();
+    let mut iterations = 0;
+
+    while iterations < MAX_ITERATIONS {
+        iterations += 1;
+        let shares: Vec<_> = scores
+            .par_iter()
+            .zip(&out_degrees)
+            .map(|(&score, &degree)| {
+                if degree > 0 {
+                    score / degree as f64
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let dangling: f64 = scores
+            .par_iter()
+            .zip(&out_degrees)
+            .filter(|&(_, &degree)| degree == 0)
+            .map(|(&score, _)| score)
+            .sum();
+
+        let next: Vec<_> = (0..n)
+            .into_par_iter()
+            .map(|i| {
+                let linked: f64 = reverse
+                    .links(i as u32)
+                    .iter()
+                    .map(|&j| shares[j as usize])
+                    .sum();
+                let kept = match params.dangling {
+                    Dangling::Teleport => dangling * teleport[i],
+                    Dangling::Drop => 0.0,
+                    Dangling::SelfLoop if out_degrees[i] == 0 => scores[i],
+                    Dangling::SelfLoop => 0.0,
+                };
+                d * (linked + kept) + (1.0 - d) * teleport[i]
+            })
+            .collect();
+
+        let change: f64 = next
+            .par_iter()
+            .zip(&scores)
+            .map(|(a, b)| (a - b).abs())
+            .sum();
+        scores = next;
+        if change < params.tolerance {
+            break;
+        }
+    }
+
+    if params.dangling == Dangling::Drop {
+        let total: f64 = scores.par_iter().sum();
+        if total > 0.0 {
+            scores.par_iter_mut().for_each(|score| *score /= total);
+        }
+    }
+    (scores, iterations)
+}
+
+/// The position of a node when sorted by score, starting at 1.
+/// Ties are broken by the page ID, so the order is reproducible.
+pub fn position(graph: &CompactGraph, scores: &[f64], node: u32) -> usize {
+    let node = node as usize;
+    let higher = (0..scores.len())
+        .into_par_iter()
+        .filter(|&i| {
+            scores[i]
+                .total_cmp(&scores[node])
+                .then(graph.pages[node].cmp(&graph.pages[i]))
+                .is_gt()
+        })
+        .count();
+    higher + 1
+}
+
+/// All nodes sorted by score, the highest first.
+pub fn ranking(graph: &CompactGraph, scores: &[f64]) -> Vec<u32> {
+    let mut order: Vec<_> = (0..scores.len() as u32).collect();
+    order.par_sort_unstable_by(|&a, &b| {
+        let (a, b) = (a as usize, b as usize);
+        scores[b]
+            .total_cmp(&scores[a])
+            .then(graph.pages[a].cmp(&graph.pages[b]))
+    });
+    order
+}
@@ -0,0 +1,194 @@
+use rand::Rng;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::centrality::bfs;
+use crate::graph::CompactGraph;
+
+/// How often the sources are resampled to find the confidence intervals.
+const BOOTSTRAP_ROUNDS: usize = 1000;
+
+/// The statistics of the distances between all pairs of distinct pages.
+#[derive(Clone, Copy)]
+pub struct Statistics {
+    /// The fraction of pairs where the second page can be reached from the first.
+    pub reachable: f64,
+    /// The mean distance between the reachable pairs.
+    pub mean: f64,
+    /// The smallest distance within which at least half of the reachable pairs are.
+    pub median: f64,
+    /// The distance within which 90% of the reachable pairs are, interpolated between steps.
+    pub effective_diameter: f64,
+}
+
+/// A value together with its 95% confidence interval.
+#[derive(Serialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+pub struct Separation {
+    pub reachable: Estimate,
+    pub mean: Estimate,
+    pub median: Estimate,
+    pub effective_diameter: Estimate,
+}
+
+/// Estimates the statistics of the distances between all pairs of pages by searching from
+/// the given sources only. The confidence intervals come from bootstrapping over the sources.
+/// Returns `None` if no source reaches any other page.
+pub fn separation(graph: &CompactGraph, sources: &[u32], rng: &mut impl Rng) -> Option<Separation> {
+    let n = graph.len();
+    let histograms: Vec<_> = {
+        let _progress = crate::progress::msg("Searching from the sampled pages");
+        sources
+            .par_iter()
+            .map_init(
+                || (vec![u32::MAX; n], Vec::new()),
+                |(dist, order), &source| {
+                    bfs(graph, source, dist, order);
+                    let mut histogram = vec![0u64; 1];
+                    for &node in &order[1..] {
+                        let d = dist[node as usize] as usize;
+                        if d >= histogram.len() {
+                            histogram.resize(d + 1, 0);
+                        }
+                        histogram[d] += 1;
+                    }
+                    for node in order.drain(..) {
+                        dist[node as usize] = u32::MAX;
+                    }
+                    histogram
+                },
+            )
+            .collect()
+    };
+    let others = n.saturating_sub(1) as u64;
+    let value = statistics(histograms.iter(), others)?;
+
+    let _progress = crate::progress::msg("Bootstrapping confidence intervals");
+    let mut rounds = Vec::with_capacity(BOOTSTRAP_ROUNDS);
+    for _ in 0..BOOTSTRAP_ROUNDS {
+        let resampled =
+            (0..histograms.len()).map(|_| &histograms[rng.gen_range(0, histograms.len())]);
+        // Rounds where only pages without links were picked have no distances at all.
+        if let Some(stats) = statistics(resampled, others) {
+            rounds.push(stats);
+        }
+    }
+    let estimate = |value: f64, stat: fn(&Statistics) -> f64| {
+        let mut values: Vec<_> = rounds.iter().map(stat).collect();
+        values.sort_unstable_by(f64::total_cmp);
+        Estimate {
+            value,
+            low: quantile(&values, 0.025),
+            high: quantile(&values, 0.975),
+        }
+    };
+    Some(Separation {
+        reachable: estimate(value.reachable, |s| s.reachable),
+        mean: estimate(value.mean, |s| s.mean),
+        median: estimate(value.median, |s| s.median),
+        effective_diameter: estimate(value.effective_diameter, |s| s.effective_diameter),
+    })
+}
+
+/// Combines the number of pages at each distance from a set of sources.
+/// Returns `None` if no pages were reached.
+fn statistics<'a>(
+    histograms: impl Iterator<Item = &'a Vec<u64>>,
+    others: u64,
+) -> Option<Statistics> {
+    let mut total = Vec::new();
+    let mut sources = 0;
+    for histogram in histograms {
+        sources += 1;
+        if histogram.len() > total.len() {
+            total.resize(histogram.len(), 0);
+        }
+        for (t, &count) in total.iter_mut().zip(histogram) {
+            *t += count;
+        }
+    }
+    let pairs: u64 = total.iter().sum();
+    if pairs == 0 {
+        return None;
+    }
+
+    let sum: u64 = total
+        .iter()
+        .enumerate()
+        .map(|(d, &count)| d as u64 * count)
+        .sum();
+    let mut median = 0.0;
+    let mut effective_diameter = 0.0;
+    let mut reached = 0;
+    for (d, &count) in total.iter().enumerate().skip(1) {
+        let before = reached as f64;
+        reached += count;
+        if median == 0.0 && 2 * reached >= pairs {
+            median = d as f64;
+        }
+        let target = 0.9 * pairs as f64;
+        if effective_diameter == 0.0 && reached as f64 >= target {
+            effective_diameter = (d - 1) as f64 + (target - before) / count as f64;
+        }
+    }
+    Some(Statistics {
+        reachable: pairs as f64 / (sources * others) as f64,
+        mean: sum as f64 / pairs as f64,
+        median,
+        effective_diameter,
+    })
+}
+
+/// The value below which the fraction `q` of the sorted values lies.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let i = (q * (sorted.len() - 1) as f64).round() as usize;
+    sorted[i]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn statistics_of_a_histogram() {
+        // 2 pages at distance 1, 5 at 2 and 3 at 3, out of 12 other pages.
+        let histograms = [vec![0, 2, 5, 3]];
+        let stats = statistics(histograms.iter(), 12).unwrap();
+        assert_close(stats.reachable, 10.0 / 12.0);
+        assert_close(stats.mean, (2.0 + 2.0 * 5.0 + 3.0 * 3.0) / 10.0);
+        // 7 of the 10 pairs are within 2 steps.
+        assert_close(stats.median, 2.0);
+        // 9 pairs are 90%: 7 are within 2 steps and 2 of the 3 at distance 3 are needed.
+        assert_close(stats.effective_diameter, 2.0 + 2.0 / 3.0);
+    }
+
+    #[test]
+    fn effective_diameter_on_a_step_boundary() {
+        // Exactly 9 of the 10 pairs are within 2 steps, so no interpolation into step 3.
+        // The second source reaches no other page.
+        let histograms = [vec![0, 1, 8, 1], vec![0]];
+        let stats = statistics(histograms.iter(), 12).unwrap();
+        assert_close(stats.reachable, 10.0 / 24.0);
+        assert_close(stats.mean, 2.0);
+        assert_close(stats.median, 2.0);
+        assert_close(stats.effective_diameter, 2.0);
+    }
+
+    #[test]
+    fn statistics_without_reached_pages() {
+        let histograms = [vec![0], vec![0]];
+        assert!(statistics(histograms.iter(), 12).is_none());
+    }
+}
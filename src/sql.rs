@@ -0,0 +1,568 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// A single literal from a row of an `INSERT` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Syntax { offset: u64, msg: String },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Syntax { offset, msg } => write!(f, "{} at byte {}", msg, offset),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Keywords starting a key definition instead of a column in `CREATE TABLE`.
+const KEY_KEYWORDS: &[&str] = &[
+    "PRIMARY",
+    "KEY",
+    "UNIQUE",
+    "INDEX",
+    "FULLTEXT",
+    "CONSTRAINT",
+];
+
+/// Streaming tokenizer for MySQL dumps as produced by `mysqldump`.
+///
+/// The column names of `CREATE TABLE` statements are recorded, other statements
+/// except `INSERT` are skipped. The rows of `INSERT` statements are returned
+/// one by one without ever holding a whole statement in memory.
+pub struct Parser<R> {
+    reader: R,
+    offset: u64,
+    in_values: bool,
+    table: String,
+    columns: HashMap<String, Vec<String>>,
+}
+
+impl<R: BufRead> Parser<R> {
+    pub fn new(reader: R) -> Self {
+        Parser {
+            reader,
+            offset: 0,
+            in_values: false,
+            table: String::new(),
+            columns: HashMap::new(),
+        }
+    }
+
+    /// The table of the `INSERT` statement the last row belongs to.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// The column names of a table, if its `CREATE TABLE` statement was already read.
+    pub fn columns(&self, table: &str) -> Option<&[String]> {
+        self.columns.get(table).map(Vec::as_slice)
+    }
+
+    pub fn next_row(&mut self) -> Result<Option<Vec<Value>>> {
+        while !self.in_values {
+            if !self.statement()? {
+                return Ok(None);
+            }
+        }
+
+        self.skip_whitespace()?;
+        self.expect(b'(')?;
+        let mut row = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            row.push(self.value()?);
+            self.skip_whitespace()?;
+            match self.next()? {
+                b',' => continue,
+                b')' => break,
+                _ => return Err(self.error("expected ',' or ')'")),
+            }
+        }
+
+        self.skip_whitespace()?;
+        match self.next()? {
+            b',' => (),
+            b';' => self.in_values = false,
+            _ => return Err(self.error("expected ',' or ';'")),
+        }
+
+        Ok(Some(row))
+    }
+
+    /// Reads the next statement up to its `VALUES` if it is an `INSERT`
+    /// or skips it entirely otherwise. Returns `false` at the end of the input.
+    fn statement(&mut self) -> Result<bool> {
+        self.skip_whitespace()?;
+        match self.peek()? {
+            None => return Ok(false),
+            Some(b';') => self.bump(),
+            Some(b'#') => self.skip_line()?,
+            Some(b'-') => {
+                self.bump();
+                self.expect(b'-')?;
+                self.skip_line()?;
+            }
+            Some(b'/') => {
+                self.bump();
+                self.expect(b'*')?;
+                self.skip_comment()?;
+            }
+            Some(_) => {
+                let keyword = self.word()?;
+                if keyword.eq_ignore_ascii_case("INSERT") {
+                    self.insert_header()?;
+                } else if keyword.eq_ignore_ascii_case("CREATE") {
+                    self.create_table()?;
+                } else {
+                    self.skip_statement()?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn insert_header(&mut self) -> Result<()> {
+        self.skip_whitespace()?;
+        if !self.word()?.eq_ignore_ascii_case("INTO") {
+            return Err(self.error("expected INTO"));
+        }
+        self.skip_whitespace()?;
+        self.table = self.identifier()?;
+        self.skip_whitespace()?;
+        if self.peek()? == Some(b'(') {
+            self.skip_parens()?;
+            self.skip_whitespace()?;
+        }
+        if !self.word()?.eq_ignore_ascii_case("VALUES") {
+            return Err(self.error("expected VALUES"));
+        }
+        self.in_values = true;
+        Ok(())
+    }
+
+    fn create_table(&mut self) -> Result<()> {
+        self.skip_whitespace()?;
+        if !self.word()?.eq_ignore_ascii_case("TABLE") {
+            return self.skip_statement();
+        }
+        self.skip_whitespace()?;
+        let mut name = self.identifier()?;
+        if name.eq_ignore_ascii_case("IF") {
+            for keyword in &["NOT", "EXISTS"] {
+                self.skip_whitespace()?;
+                if !self.word()?.eq_ignore_ascii_case(keyword) {
+                    return Err(self.error(&format!("expected {}", keyword)));
+                }
+            }
+            self.skip_whitespace()?;
+            name = self.identifier()?;
+        }
+        self.skip_whitespace()?;
+        self.expect(b'(')?;
+
+        let mut columns = Vec::new();
+        loop {
+            self.skip_whitespace()?;
+            let quoted = self.peek()? == Some(b'`');
+            let first = self.identifier()?;
+            let is_constraint = KEY_KEYWORDS.iter().any(|k| first.eq_ignore_ascii_case(k));
+            if quoted || !is_constraint {
+                columns.push(first);
+            }
+            if self.skip_definition()? {
+                break;
+            }
+        }
+
+        self.columns.insert(name, columns);
+        self.skip_statement()
+    }
+
+    /// Skips the rest of a column or key definition.
+    /// Returns `true` if it was the last one of the table.
+    fn skip_definition(&mut self) -> Result<bool> {
+        loop {
+            match self.next()? {
+                b',' => return Ok(false),
+                b')' => return Ok(true),
+                b'(' => {
+                    self.skip_parens_from(1)?;
+                }
+                c @ b'\'' | c @ b'"' | c @ b'`' => {
+                    self.string(c)?;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        match self.peek()? {
+            Some(b'\'') => {
+                self.bump();
+                self.string(b'\'').map(Value::Str)
+            }
+            Some(b'"') => {
+                self.bump();
+                self.string(b'"').map(Value::Str)
+            }
+            Some(b'-') | Some(b'+') | Some(b'.') | Some(b'0'..=b'9') => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let word = self.word()?;
+                if word.eq_ignore_ascii_case("NULL") {
+                    Ok(Value::Null)
+                } else {
+                    Err(self.error(&format!("unexpected literal '{}'", word)))
+                }
+            }
+            _ => Err(self.error("expected value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value> {
+        let mut text = String::new();
+        while let Some(c) = self.peek()? {
+            match c {
+                b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E' => {
+                    text.push(c as char);
+                    self.bump();
+                }
+                _ => break,
+            }
+        }
+        if let Ok(i) = text.parse() {
+            Ok(Value::Int(i))
+        } else if let Ok(x) = text.parse() {
+            Ok(Value::Float(x))
+        } else {
+            Err(self.error(&format!("invalid number '{}'", text)))
+        }
+    }
+
+    /// Reads the rest of a quoted string, resolving MySQL escape sequences.
+    fn string(&mut self, quote: u8) -> Result<String> {
+        let mut bytes = Vec::new();
+        loop {
+            let buf = self.reader.fill_buf()?;
+            if buf.is_empty() {
+                return Err(self.error("unterminated string"));
+            }
+            match buf.iter().position(|&c| c == quote || c == b'\\') {
+                None => {
+                    bytes.extend_from_slice(buf);
+                    let len = buf.len();
+                    self.consume(len);
+                }
+                Some(pos) => {
+                    bytes.extend_from_slice(&buf[..pos]);
+                    let c = buf[pos];
+                    self.consume(pos + 1);
+                    if c == b'\\' {
+                        bytes.push(match self.next()? {
+                            b'0' => 0,
+                            b'b' => 8,
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'Z' => 26,
+                            c => c,
+                        });
+                    } else if self.peek()? == Some(quote) {
+                        self.bump();
+                        bytes.push(quote);
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        })
+    }
+
+    fn identifier(&mut self) -> Result<String> {
+        if self.peek()? == Some(b'`') {
+            self.bump();
+            self.string(b'`')
+        } else {
+            self.word()
+        }
+    }
+
+    fn word(&mut self) -> Result<String> {
+        let mut word = String::new();
+        while let Some(c) = self.peek()? {
+            if c.is_ascii_alphanumeric() || c == b'_' {
+                word.push(c as char);
+                self.bump();
+            } else {
+                break;
+            }
+        }
+        if word.is_empty() {
+            return Err(self.error("expected keyword"));
+        }
+        Ok(word)
+    }
+
+    fn skip_statement(&mut self) -> Result<()> {
+        loop {
+            match self.next()? {
+                b';' => return Ok(()),
+                c @ b'\'' | c @ b'"' | c @ b'`' => {
+                    self.string(c)?;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn skip_parens(&mut self) -> Result<()> {
+        self.expect(b'(')?;
+        self.skip_parens_from(1)
+    }
+
+    fn skip_parens_from(&mut self, mut depth: usize) -> Result<()> {
+        loop {
+            match self.next()? {
+                b'(' => depth += 1,
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                c @ b'\'' | c @ b'"' | c @ b'`' => {
+                    self.string(c)?;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn skip_line(&mut self) -> Result<()> {
+        while let Some(c) = self.peek()? {
+            self.bump();
+            if c == b'\n' {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn skip_comment(&mut self) -> Result<()> {
+        loop {
+            if self.next()? == b'*' && self.peek()? == Some(b'/') {
+                self.bump();
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<()> {
+        while let Some(c) = self.peek()? {
+            if !c.is_ascii_whitespace() {
+                break;
+            }
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        if self.next()? != expected {
+            return Err(self.error(&format!("expected '{}'", expected as char)));
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn next(&mut self) -> Result<u8> {
+        match self.peek()? {
+            Some(c) => {
+                self.bump();
+                Ok(c)
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn bump(&mut self) {
+        self.consume(1);
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount);
+        self.offset += amount as u64;
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        Error::Syntax {
+            offset: self.offset,
+            msg: msg.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(sql: &str) -> Vec<Vec<Value>> {
+        let mut parser = Parser::new(sql.as_bytes());
+        let mut rows = Vec::new();
+        while let Some(row) = parser.next_row().unwrap() {
+            rows.push(row);
+        }
+        rows
+    }
+
+    fn string(s: &str) -> Value {
+        Value::Str(s.to_owned())
+    }
+
+    #[test]
+    fn parentheses_and_commas_in_strings() {
+        let rows =
+            rows("INSERT INTO `page` VALUES (1,'Seesterne_(Klasse),(Art),(Gattung)',0),(2,'B',1);");
+        assert_eq!(
+            rows,
+            vec![
+                vec![
+                    Value::Int(1),
+                    string("Seesterne_(Klasse),(Art),(Gattung)"),
+                    Value::Int(0)
+                ],
+                vec![Value::Int(2), string("B"), Value::Int(1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn escapes() {
+        let rows = rows(r#"INSERT INTO t VALUES ('It\'s','It''s',"say ""hi""",'a\\b','\n\0');"#);
+        assert_eq!(
+            rows,
+            vec![vec![
+                string("It's"),
+                string("It's"),
+                string("say \"hi\""),
+                string("a\\b"),
+                string("\n\0"),
+            ]]
+        );
+    }
+
+    #[test]
+    fn literals() {
+        let rows = rows("INSERT INTO t VALUES (NULL,null,-12,3.5,-0.25,1e3);");
+        assert_eq!(
+            rows,
+            vec![vec![
+                Value::Null,
+                Value::Null,
+                Value::Int(-12),
+                Value::Float(3.5),
+                Value::Float(-0.25),
+                Value::Float(1000.0),
+            ]]
+        );
+    }
+
+    #[test]
+    fn comments_and_other_statements() {
+        let sql = "\
+-- MySQL dump 10.19
+/*!40101 SET @OLD_CHARACTER_SET_CLIENT=@@CHARACTER_SET_CLIENT */;
+# a comment
+/* plain; comment */
+DROP TABLE IF EXISTS `t`;
+LOCK TABLES `t` WRITE;
+/*!40000 ALTER TABLE `t` DISABLE KEYS */;
+INSERT INTO `t` VALUES (1),(2);
+INSERT INTO `t` (`a`) VALUES (3);
+/*!40000 ALTER TABLE `t` ENABLE KEYS */;
+UNLOCK TABLES;
+";
+        let mut parser = Parser::new(sql.as_bytes());
+        let mut values = Vec::new();
+        while let Some(row) = parser.next_row().unwrap() {
+            assert_eq!(parser.table(), "t");
+            values.extend(row);
+        }
+        assert_eq!(values, vec![Value::Int(1), Value::Int(2), Value::Int(3)]);
+    }
+
+    #[test]
+    fn create_table_columns() {
+        let sql = "\
+CREATE TABLE `pagelinks` (
+  `pl_from` int(8) unsigned NOT NULL DEFAULT '0',
+  `pl_namespace` int(11) NOT NULL DEFAULT '0',
+  `pl_title` varbinary(255) NOT NULL DEFAULT '',
+  `pl_comment` varchar(10) DEFAULT 'a,b)',
+  PRIMARY KEY (`pl_from`,`pl_namespace`,`pl_title`),
+  UNIQUE KEY `pl_x` (`pl_title`),
+  KEY `pl_namespace` (`pl_namespace`,`pl_title`,`pl_from`)
+) ENGINE=InnoDB DEFAULT CHARSET=binary;
+CREATE TABLE IF NOT EXISTS `key` (
+  `key` int NOT NULL,
+  value int,
+  KEY `k` (`key`)
+);
+INSERT INTO `pagelinks` VALUES (1,0,'A','');
+";
+        let mut parser = Parser::new(sql.as_bytes());
+        assert_eq!(parser.next_row().unwrap().unwrap().len(), 4);
+        assert_eq!(
+            parser.columns("pagelinks").unwrap(),
+            ["pl_from", "pl_namespace", "pl_title", "pl_comment"]
+        );
+        assert_eq!(parser.columns("key").unwrap(), ["key", "value"]);
+        assert_eq!(parser.next_row().unwrap(), None);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let mut parser = Parser::new("INSERT INTO t VALUES (1,'open);".as_bytes());
+        assert!(matches!(parser.next_row(), Err(Error::Syntax { .. })));
+        let mut parser = Parser::new("INSERT INTO t VALUES (1 2);".as_bytes());
+        assert!(matches!(parser.next_row(), Err(Error::Syntax { .. })));
+    }
+}
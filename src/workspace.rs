@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The directory holding the extracted and generated files of one wiki dump,
+/// e.g. `data/enwiki-20261001/` for the dumps `data/enwiki-20261001-*.sql.gz`.
+pub struct Workspace {
+    /// The dump prefix, i.e. the wiki name and the dump date.
+    pub name: String,
+    data_dir: PathBuf,
+    dir: PathBuf,
+}
+
+impl Workspace {
+    /// Selects the wiki to work on by its dump prefix.
+    ///
+    /// A prefix without a date like `enwiki` selects the latest dump of that wiki.
+    /// Without a prefix there must only be a single wiki in the data directory.
+    pub fn select(data_dir: &Path, prefix: Option<&str>) -> Result<Workspace, ()> {
+        let mut names = available(data_dir);
+        names.retain(|name| match prefix {
+            Some(prefix) => name == prefix || name.starts_with(&format!("{}-", prefix)),
+            None => true,
+        });
+
+        let name = match (prefix, names.len()) {
+            (_, 0) => {
+                match prefix {
+                    Some(prefix) => eprintln!("No dumps found for wiki '{}'.", prefix),
+                    None => eprintln!("Missing database dumps."),
+                }
+                eprintln!("Please download the 3 tables 'page', 'pagelinks' and 'redirect'");
+                eprintln!("from https://dumps.wikimedia.org/ as .sql.gz files,");
+                eprintln!(
+                    "place them in the '{}' directory and try again.",
+                    data_dir.display()
+                );
+                return Err(());
+            }
+            (Some(_), _) | (None, 1) => names.pop().unwrap(),
+            (None, _) => {
+                eprintln!("Dumps of multiple wikis found:");
+                for name in &names {
+                    eprintln!("{}", name);
+                }
+                eprintln!("Please choose one with '--wiki'.");
+                return Err(());
+            }
+        };
+
+        Ok(Workspace::new(data_dir, name))
+    }
+
+    fn new(data_dir: &Path, name: String) -> Workspace {
+        Workspace {
+            dir: data_dir.join(&name),
+            data_dir: data_dir.to_owned(),
+            name,
+        }
+    }
+
+    /// The language code of the wiki as used in `langlinks`, e.g. `de` for `dewiki`.
+    pub fn lang(&self) -> String {
+        let wiki = self.name.split('-').next().unwrap();
+        wiki.trim_end_matches("wiki").replace('_', "-")
+    }
+
+    /// The path of a file in the workspace directory, which is created if necessary.
+    pub fn path(&self, file: &str) -> PathBuf {
+        std::fs::create_dir_all(&self.dir).unwrap();
+        self.dir.join(file)
+    }
+
+    /// The namespaces the link graph in the workspace was generated for.
+    /// Workspaces from before they were recorded always contain articles only.
+    pub fn namespaces(&self) -> HashSet<i64> {
+        match std::fs::read_to_string(self.path(crate::NAMESPACES)) {
+            Ok(list) => list
+                .trim()
+                .split(',')
+                .map(|ns| ns.parse().unwrap())
+                .collect(),
+            Err(_) => [0].iter().copied().collect(),
+        }
+    }
+
+    pub fn save_namespaces(&self, namespaces: &HashSet<i64>) {
+        std::fs::write(self.path(crate::NAMESPACES), namespace_list(namespaces)).unwrap();
+    }
+
+    /// The dump of a table, either next to the workspace directory or inside of it.
+    pub fn dump(&self, table: &str) -> Option<PathBuf> {
+        let file = format!("{}-{}.sql.gz", self.name, table);
+        [self.data_dir.join(&file), self.dir.join(&file)]
+            .iter()
+            .find(|path| path.exists())
+            .cloned()
+    }
+}
+
+/// The namespaces sorted and separated by commas, as given to `--namespaces`.
+pub fn namespace_list(namespaces: &HashSet<i64>) -> String {
+    let mut namespaces: Vec<_> = namespaces.iter().collect();
+    namespaces.sort_unstable();
+    let namespaces: Vec<_> = namespaces.iter().map(|ns| ns.to_string()).collect();
+    namespaces.join(",")
+}
+
+/// The names of all wikis with dumps or generated files in the data directory, sorted by name.
+fn available(data_dir: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    let entries = match std::fs::read_dir(data_dir) {
+        Ok(entries) => entries,
+        Err(_) => return names,
+    };
+
+    for entry in entries {
+        let entry = entry.unwrap();
+        let file_name = entry.file_name().into_string().unwrap();
+        let name = if let Some(name) = file_name.strip_suffix("-page.sql.gz") {
+            name.to_owned()
+        } else if entry.path().join(crate::PAGES).exists()
+            || entry
+                .path()
+                .join(format!("{}-page.sql.gz", file_name))
+                .exists()
+        {
+            file_name
+        } else {
+            continue;
+        };
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names.sort();
+    names
+}